@@ -0,0 +1,102 @@
+use super::Cpu;
+use crate::mem::Bus;
+use crate::opcodes::AddressingMode;
+use crate::{Byte, Word};
+
+impl Cpu {
+    /// Fetches the operand bytes for `mode` and returns the effective address, plus
+    /// whether indexing or a branch crossed a page.
+    ///
+    /// Zero page indexing and the pointers read by the indirect modes wrap within page
+    /// zero; everything else wraps at $FFFF. Immediate operands are addressed at PC,
+    /// branches resolve to their target, and `ZeroPageRelative` resolves only the zero
+    /// page operand, leaving the branch offset to the instruction. Implied and
+    /// accumulator modes fetch nothing and return PC.
+    pub(super) fn resolve_address<B: Bus>(
+        &mut self,
+        mode: AddressingMode,
+        memory: &mut B,
+    ) -> (Word, bool) {
+        match mode {
+            AddressingMode::Implied | AddressingMode::Accumulator => (self.PC, false),
+            AddressingMode::Immediate => {
+                let address: Word = self.PC;
+                self.PC = self.PC.wrapping_add(1);
+                (address, false)
+            }
+            AddressingMode::ZeroPage | AddressingMode::ZeroPageRelative => {
+                (self.fetch_byte(memory).into(), false)
+            }
+            AddressingMode::ZeroPageX => (self.fetch_zero_page_indexed(self.X, memory), false),
+            AddressingMode::ZeroPageY => (self.fetch_zero_page_indexed(self.Y, memory), false),
+            AddressingMode::Relative => {
+                let offset: i8 = self.fetch_byte(memory) as i8;
+                let target: Word = self.PC.wrapping_add(offset as Word);
+                (target, Self::crosses_page(self.PC, target))
+            }
+            AddressingMode::Absolute => (self.fetch_word(memory), false),
+            AddressingMode::AbsoluteX => {
+                let base_address: Word = self.fetch_word(memory);
+                Self::index(base_address, self.X)
+            }
+            AddressingMode::AbsoluteY => {
+                let base_address: Word = self.fetch_word(memory);
+                Self::index(base_address, self.Y)
+            }
+            AddressingMode::Indirect => {
+                let pointer: Word = self.fetch_word(memory);
+                let high_byte_address: Word = if self.variant.has_jmp_indirect_bug() {
+                    (pointer & 0xFF00) | (pointer.wrapping_add(1) & 0x00FF)
+                } else {
+                    pointer.wrapping_add(1)
+                };
+                let low_byte = self.read_byte(pointer, memory) as Word;
+                let high_byte = self.read_byte(high_byte_address, memory) as Word;
+                ((high_byte << 8) | low_byte, false)
+            }
+            AddressingMode::IndirectX => {
+                let zero_page_address: Byte = self.fetch_byte(memory).wrapping_add(self.X);
+                (self.read_zero_page_word(zero_page_address, memory), false)
+            }
+            AddressingMode::IndirectY => {
+                let zero_page_address: Byte = self.fetch_byte(memory);
+                let base_address: Word = self.read_zero_page_word(zero_page_address, memory);
+                Self::index(base_address, self.Y)
+            }
+            AddressingMode::ZeroPageIndirect => {
+                let zero_page_address: Byte = self.fetch_byte(memory);
+                (self.read_zero_page_word(zero_page_address, memory), false)
+            }
+            AddressingMode::AbsoluteIndirectX => {
+                let pointer: Word = self.fetch_word(memory).wrapping_add(self.X as Word);
+                (self.read_word(pointer, memory), false)
+            }
+        }
+    }
+
+    /// Fetches a zero page operand and adds `index` to it, wrapping within page zero.
+    fn fetch_zero_page_indexed<B: Bus>(&mut self, index: Byte, memory: &mut B) -> Word {
+        let zero_page_address: Byte = self.fetch_byte(memory).wrapping_add(index);
+        zero_page_address.into()
+    }
+
+    /// Reads a little-endian pointer from page zero. A pointer at $FF takes its high
+    /// byte from $00, not $0100.
+    fn read_zero_page_word<B: Bus>(&mut self, zero_page_address: Byte, memory: &mut B) -> Word {
+        let low_byte = self.read_byte(zero_page_address.into(), memory) as Word;
+        let high_byte = self.read_byte(zero_page_address.wrapping_add(1).into(), memory) as Word;
+        (high_byte << 8) | low_byte
+    }
+
+    fn index(base_address: Word, index: Byte) -> (Word, bool) {
+        let effective_address: Word = base_address.wrapping_add(index.into());
+        (
+            effective_address,
+            Self::crosses_page(base_address, effective_address),
+        )
+    }
+
+    fn crosses_page(from: Word, to: Word) -> bool {
+        from & 0xFF00 != to & 0xFF00
+    }
+}
@@ -0,0 +1,161 @@
+use super::Cpu;
+use crate::mem::Bus;
+use crate::opcodes::{AddressingMode, OpCode};
+use crate::{Byte, Word};
+
+impl Cpu {
+    /// Executes one of the opcodes the 65C02 added or redefined. Every opcode the
+    /// 65C02 leaves unused is a NOP, so this handles everything the shared arms of
+    /// `step` do not.
+    pub(super) fn execute_cmos<B: Bus>(
+        &mut self,
+        instruction: Byte,
+        address: Word,
+        memory: &mut B,
+    ) {
+        match instruction {
+            OpCode::INS_BRA => {
+                self.branch(true, address);
+            }
+            OpCode::INS_STZ_ZP
+            | OpCode::INS_STZ_ZPX
+            | OpCode::INS_STZ_ABS
+            | OpCode::INS_STZ_ABSX => {
+                self.write_byte(0, address, memory);
+            }
+            OpCode::INS_PHX => {
+                self.push_byte(self.X, memory);
+            }
+            OpCode::INS_PHY => {
+                self.push_byte(self.Y, memory);
+            }
+            OpCode::INS_PLX => {
+                self.X = self.pull_byte(memory);
+                self.LDXSetStatus();
+            }
+            OpCode::INS_PLY => {
+                self.Y = self.pull_byte(memory);
+                self.LDYSetStatus();
+            }
+            OpCode::INS_TRB_ZP | OpCode::INS_TRB_ABS => {
+                self.read_modify_write(address, Self::trb, memory);
+            }
+            OpCode::INS_TSB_ZP | OpCode::INS_TSB_ABS => {
+                self.read_modify_write(address, Self::tsb, memory);
+            }
+            OpCode::INS_ORA_INDZP => self.cmos_read(address, Self::ora, memory),
+            OpCode::INS_AND_INDZP => self.cmos_read(address, Self::and, memory),
+            OpCode::INS_EOR_INDZP => self.cmos_read(address, Self::eor, memory),
+            OpCode::INS_ADC_INDZP => self.cmos_read(address, Self::adc, memory),
+            OpCode::INS_LDA_INDZP => self.cmos_read(address, Self::lda, memory),
+            OpCode::INS_CMP_INDZP => self.cmos_read(address, Self::compare_a, memory),
+            OpCode::INS_SBC_INDZP => self.cmos_read(address, Self::sbc, memory),
+            OpCode::INS_STA_INDZP => {
+                self.write_byte(self.A, address, memory);
+            }
+            OpCode::INS_BIT_IM => {
+                // Immediate BIT has no memory operand to copy N and V from.
+                let value: Byte = self.read_byte(address, memory);
+                self.Z = if self.A & value == 0 { 1 } else { 0 };
+            }
+            OpCode::INS_BIT_ZPX | OpCode::INS_BIT_ABSX => {
+                self.cmos_read(address, Self::bit, memory)
+            }
+            OpCode::INS_INC_ACC => {
+                self.A = self.inc(self.A);
+            }
+            OpCode::INS_DEC_ACC => {
+                self.A = self.dec(self.A);
+            }
+            OpCode::INS_JMP_ABSX_IND => {
+                self.PC = address;
+            }
+
+            OpCode::INS_RMB0
+            | OpCode::INS_RMB1
+            | OpCode::INS_RMB2
+            | OpCode::INS_RMB3
+            | OpCode::INS_RMB4
+            | OpCode::INS_RMB5
+            | OpCode::INS_RMB6
+            | OpCode::INS_RMB7
+            | OpCode::INS_SMB0
+            | OpCode::INS_SMB1
+            | OpCode::INS_SMB2
+            | OpCode::INS_SMB3
+            | OpCode::INS_SMB4
+            | OpCode::INS_SMB5
+            | OpCode::INS_SMB6
+            | OpCode::INS_SMB7
+                if self.variant.has_bit_instructions() =>
+            {
+                // Bits 4-6 of the opcode select the bit, bit 7 selects set or reset.
+                let mask: Byte = 1 << ((instruction >> 4) & 0x07);
+                let value: Byte = self.read_byte(address, memory);
+                self.read_byte(address, memory);
+                let result: Byte = if instruction & 0x80 != 0 {
+                    value | mask
+                } else {
+                    value & !mask
+                };
+                self.write_byte(result, address, memory);
+            }
+            OpCode::INS_BBR0
+            | OpCode::INS_BBR1
+            | OpCode::INS_BBR2
+            | OpCode::INS_BBR3
+            | OpCode::INS_BBR4
+            | OpCode::INS_BBR5
+            | OpCode::INS_BBR6
+            | OpCode::INS_BBR7
+            | OpCode::INS_BBS0
+            | OpCode::INS_BBS1
+            | OpCode::INS_BBS2
+            | OpCode::INS_BBS3
+            | OpCode::INS_BBS4
+            | OpCode::INS_BBS5
+            | OpCode::INS_BBS6
+            | OpCode::INS_BBS7
+                if self.variant.has_bit_instructions() =>
+            {
+                let mask: Byte = 1 << ((instruction >> 4) & 0x07);
+                let branch_if_set: bool = instruction & 0x80 != 0;
+                let value: Byte = self.read_byte(address, memory);
+                let (target, page_crossed) = self.resolve_address(AddressingMode::Relative, memory);
+                self.page_crossed = page_crossed;
+                self.branch((value & mask != 0) == branch_if_set, target);
+            }
+            OpCode::INS_WAI if self.variant.has_wait_and_stop() => {
+                self.waiting = true;
+            }
+            OpCode::INS_STP if self.variant.has_wait_and_stop() => {
+                self.jammed = true;
+            }
+            // The unused opcodes are NOPs; their length and timing come from the
+            // opcode table.
+            _ => {}
+        }
+    }
+
+    fn cmos_read<B: Bus>(&mut self, address: Word, operation: fn(&mut Self, Byte), memory: &mut B) {
+        let value: Byte = self.read_byte(address, memory);
+        operation(self, value);
+    }
+
+    fn lda(&mut self, value: Byte) {
+        self.A = value;
+        self.LDASetStatus();
+    }
+
+    /// Z is set from `A & value`, then the bits set in A are cleared in memory.
+    fn trb(&mut self, value: Byte) -> Byte {
+        self.Z = if self.A & value == 0 { 1 } else { 0 };
+        value & !self.A
+    }
+
+    /// Z is set from `A & value`, then the bits set in A are set in memory.
+    fn tsb(&mut self, value: Byte) -> Byte {
+        self.Z = if self.A & value == 0 { 1 } else { 0 };
+        value | self.A
+    }
+}
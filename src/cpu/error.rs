@@ -0,0 +1,23 @@
+use crate::{Byte, Word};
+use std::error::Error;
+use std::fmt;
+
+/// Errors that stop the CPU from running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuError {
+    /// An undocumented opcode was fetched while the policy is
+    /// `IllegalOpcodePolicy::Halt`. PC is left pointing at the opcode.
+    IllegalOpcode { pc: Word, opcode: Byte },
+}
+
+impl fmt::Display for CpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CpuError::IllegalOpcode { pc, opcode } => {
+                write!(f, "illegal opcode ${:02X} at ${:04X}", opcode, pc)
+            }
+        }
+    }
+}
+
+impl Error for CpuError {}
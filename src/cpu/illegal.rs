@@ -0,0 +1,311 @@
+use super::Cpu;
+use crate::mem::Bus;
+use crate::opcodes::OpCode;
+use crate::{Byte, Word};
+
+/// What to do when the CPU fetches an opcode outside the documented instruction set of
+/// its variant (see `OpCode::is_documented`).
+#[derive(Default)]
+pub enum IllegalOpcodePolicy {
+    /// Run it the way the silicon does: the undocumented NMOS opcodes do their thing
+    /// and the reserved 65C02 opcodes are NOPs.
+    #[default]
+    Execute,
+    /// Stop and return `CpuError::IllegalOpcode`.
+    Halt,
+    /// Skip it as a NOP of the length the opcode table gives it.
+    Nop,
+    /// Lock up like the NMOS JAM opcodes until the next reset.
+    Jam,
+    /// Ask the callback, which gets PC of the opcode and the opcode itself.
+    Callback(Box<dyn FnMut(Word, Byte) -> IllegalOpcodeAction>),
+}
+
+/// The choice an `IllegalOpcodePolicy::Callback` makes for one opcode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IllegalOpcodeAction {
+    Execute,
+    Halt,
+    Nop,
+    Jam,
+}
+
+impl Cpu {
+    /// Applies the illegal opcode policy to `opcode`, fetched from `pc`.
+    pub(super) fn illegal_opcode_action(&mut self, pc: Word, opcode: Byte) -> IllegalOpcodeAction {
+        match &mut self.illegal_opcode_policy {
+            IllegalOpcodePolicy::Execute => IllegalOpcodeAction::Execute,
+            IllegalOpcodePolicy::Halt => IllegalOpcodeAction::Halt,
+            IllegalOpcodePolicy::Nop => IllegalOpcodeAction::Nop,
+            IllegalOpcodePolicy::Jam => IllegalOpcodeAction::Jam,
+            IllegalOpcodePolicy::Callback(callback) => callback(pc, opcode),
+        }
+    }
+
+    /// Executes one of the undocumented NMOS opcodes, with `address` already resolved
+    /// from the opcode table.
+    pub(super) fn execute_illegal<B: Bus>(
+        &mut self,
+        instruction: Byte,
+        address: Word,
+        memory: &mut B,
+    ) {
+        match instruction {
+            OpCode::INS_SLO_ZP
+            | OpCode::INS_SLO_ZPX
+            | OpCode::INS_SLO_ABS
+            | OpCode::INS_SLO_ABSX
+            | OpCode::INS_SLO_ABSY
+            | OpCode::INS_SLO_INDX
+            | OpCode::INS_SLO_INDY => self.read_modify_write(address, Self::slo, memory),
+
+            OpCode::INS_RLA_ZP
+            | OpCode::INS_RLA_ZPX
+            | OpCode::INS_RLA_ABS
+            | OpCode::INS_RLA_ABSX
+            | OpCode::INS_RLA_ABSY
+            | OpCode::INS_RLA_INDX
+            | OpCode::INS_RLA_INDY => self.read_modify_write(address, Self::rla, memory),
+
+            OpCode::INS_SRE_ZP
+            | OpCode::INS_SRE_ZPX
+            | OpCode::INS_SRE_ABS
+            | OpCode::INS_SRE_ABSX
+            | OpCode::INS_SRE_ABSY
+            | OpCode::INS_SRE_INDX
+            | OpCode::INS_SRE_INDY => self.read_modify_write(address, Self::sre, memory),
+
+            OpCode::INS_RRA_ZP
+            | OpCode::INS_RRA_ZPX
+            | OpCode::INS_RRA_ABS
+            | OpCode::INS_RRA_ABSX
+            | OpCode::INS_RRA_ABSY
+            | OpCode::INS_RRA_INDX
+            | OpCode::INS_RRA_INDY => self.read_modify_write(address, Self::rra, memory),
+
+            OpCode::INS_DCP_ZP
+            | OpCode::INS_DCP_ZPX
+            | OpCode::INS_DCP_ABS
+            | OpCode::INS_DCP_ABSX
+            | OpCode::INS_DCP_ABSY
+            | OpCode::INS_DCP_INDX
+            | OpCode::INS_DCP_INDY => self.read_modify_write(address, Self::dcp, memory),
+
+            OpCode::INS_ISC_ZP
+            | OpCode::INS_ISC_ZPX
+            | OpCode::INS_ISC_ABS
+            | OpCode::INS_ISC_ABSX
+            | OpCode::INS_ISC_ABSY
+            | OpCode::INS_ISC_INDX
+            | OpCode::INS_ISC_INDY => self.read_modify_write(address, Self::isc, memory),
+
+            OpCode::INS_LAX_ZP
+            | OpCode::INS_LAX_ZPY
+            | OpCode::INS_LAX_ABS
+            | OpCode::INS_LAX_ABSY
+            | OpCode::INS_LAX_INDX
+            | OpCode::INS_LAX_INDY => {
+                let value: Byte = self.read_byte(address, memory);
+                self.lax(value);
+            }
+
+            OpCode::INS_SAX_ZP
+            | OpCode::INS_SAX_ZPY
+            | OpCode::INS_SAX_ABS
+            | OpCode::INS_SAX_INDX => {
+                self.write_byte(self.A & self.X, address, memory);
+            }
+
+            OpCode::INS_ANC_IM | OpCode::INS_ANC_IM_2B => {
+                let value: Byte = self.read_byte(address, memory);
+                self.and(value);
+                self.C = self.N;
+            }
+            OpCode::INS_ALR_IM => {
+                let value: Byte = self.read_byte(address, memory);
+                self.A = self.lsr(self.A & value);
+            }
+            OpCode::INS_ARR_IM => {
+                let value: Byte = self.read_byte(address, memory);
+                self.arr(value);
+            }
+            OpCode::INS_SBX_IM => {
+                let value: Byte = self.read_byte(address, memory);
+                let masked: Byte = self.A & self.X;
+                self.C = if masked >= value { 1 } else { 0 };
+                self.X = masked.wrapping_sub(value);
+                self.LDXSetStatus();
+            }
+            OpCode::INS_SBC_IM_EB => {
+                let value: Byte = self.read_byte(address, memory);
+                self.sbc(value);
+            }
+
+            OpCode::INS_ANE_IM => {
+                let value: Byte = self.read_byte(address, memory);
+                self.A = (self.A | self.unstable_magic) & self.X & value;
+                self.LDASetStatus();
+            }
+            OpCode::INS_LXA_IM => {
+                let value: Byte = self.read_byte(address, memory);
+                self.A = (self.A | self.unstable_magic) & value;
+                self.X = self.A;
+                self.LDASetStatus();
+            }
+            OpCode::INS_SHA_ABSY | OpCode::INS_SHA_INDY => {
+                self.illegal_sh(address, self.Y, self.A & self.X, memory);
+            }
+            OpCode::INS_SHX_ABSY => {
+                self.illegal_sh(address, self.Y, self.X, memory);
+            }
+            OpCode::INS_SHY_ABSX => {
+                self.illegal_sh(address, self.X, self.Y, memory);
+            }
+            OpCode::INS_TAS_ABSY => {
+                self.SP = self.A & self.X;
+                self.illegal_sh(address, self.Y, self.SP, memory);
+            }
+            OpCode::INS_LAS_ABSY => {
+                let value: Byte = self.read_byte(address, memory) & self.SP;
+                self.A = value;
+                self.X = value;
+                self.SP = value;
+                self.LDASetStatus();
+            }
+
+            // Implied and immediate NOPs only skip their operand; the others also
+            // perform the read.
+            OpCode::INS_NOP_1A
+            | OpCode::INS_NOP_3A
+            | OpCode::INS_NOP_5A
+            | OpCode::INS_NOP_7A
+            | OpCode::INS_NOP_DA
+            | OpCode::INS_NOP_FA
+            | OpCode::INS_NOP_IM_80
+            | OpCode::INS_NOP_IM_82
+            | OpCode::INS_NOP_IM_89
+            | OpCode::INS_NOP_IM_C2
+            | OpCode::INS_NOP_IM_E2 => {}
+            OpCode::INS_NOP_ZP_04
+            | OpCode::INS_NOP_ZP_44
+            | OpCode::INS_NOP_ZP_64
+            | OpCode::INS_NOP_ZPX_14
+            | OpCode::INS_NOP_ZPX_34
+            | OpCode::INS_NOP_ZPX_54
+            | OpCode::INS_NOP_ZPX_74
+            | OpCode::INS_NOP_ZPX_D4
+            | OpCode::INS_NOP_ZPX_F4
+            | OpCode::INS_NOP_ABS_0C
+            | OpCode::INS_NOP_ABSX_1C
+            | OpCode::INS_NOP_ABSX_3C
+            | OpCode::INS_NOP_ABSX_5C
+            | OpCode::INS_NOP_ABSX_7C
+            | OpCode::INS_NOP_ABSX_DC
+            | OpCode::INS_NOP_ABSX_FC => {
+                self.read_byte(address, memory);
+            }
+
+            OpCode::INS_JAM_02
+            | OpCode::INS_JAM_12
+            | OpCode::INS_JAM_22
+            | OpCode::INS_JAM_32
+            | OpCode::INS_JAM_42
+            | OpCode::INS_JAM_52
+            | OpCode::INS_JAM_62
+            | OpCode::INS_JAM_72
+            | OpCode::INS_JAM_92
+            | OpCode::INS_JAM_B2
+            | OpCode::INS_JAM_D2
+            | OpCode::INS_JAM_F2 => {
+                self.jammed = true;
+            }
+            _ => unreachable!("opcode {:#04x} is documented", instruction),
+        }
+    }
+
+    /// SHA, SHX, SHY and TAS store `value & (H + 1)`, where H is the high byte of the
+    /// base address before `index` was added. When indexing crossed a page the stored
+    /// value also replaces the high byte of the target address.
+    fn illegal_sh<B: Bus>(
+        &mut self,
+        effective_address: Word,
+        index: Byte,
+        value: Byte,
+        memory: &mut B,
+    ) {
+        let base_address: Word = effective_address.wrapping_sub(index.into());
+        let result: Byte = value & ((base_address >> 8) as Byte).wrapping_add(1);
+        let address: Word = if self.page_crossed {
+            ((result as Word) << 8) | (effective_address & 0x00FF)
+        } else {
+            effective_address
+        };
+        self.write_byte(result, address, memory);
+    }
+
+    fn lax(&mut self, value: Byte) {
+        self.A = value;
+        self.X = value;
+        self.LDASetStatus();
+    }
+
+    fn slo(&mut self, value: Byte) -> Byte {
+        let result: Byte = self.asl(value);
+        self.ora(result);
+        result
+    }
+
+    fn rla(&mut self, value: Byte) -> Byte {
+        let result: Byte = self.rol(value);
+        self.and(result);
+        result
+    }
+
+    fn sre(&mut self, value: Byte) -> Byte {
+        let result: Byte = self.lsr(value);
+        self.eor(result);
+        result
+    }
+
+    fn rra(&mut self, value: Byte) -> Byte {
+        let result: Byte = self.ror(value);
+        self.adc(result);
+        result
+    }
+
+    fn dcp(&mut self, value: Byte) -> Byte {
+        let result: Byte = value.wrapping_sub(1);
+        self.compare_a(result);
+        result
+    }
+
+    fn isc(&mut self, value: Byte) -> Byte {
+        let result: Byte = value.wrapping_add(1);
+        self.sbc(result);
+        result
+    }
+
+    /// AND followed by ROR A, with C and V taken from bits 6 and 5 of the result. In
+    /// decimal mode the result is BCD corrected and C reflects the high nibble fix-up;
+    /// the 2A03, which has no decimal mode, always takes the binary path.
+    fn arr(&mut self, value: Byte) {
+        let masked: Byte = self.A & value;
+        self.A = (masked >> 1) | (self.C << 7);
+        self.LDASetStatus();
+        if !self.decimal_mode() {
+            self.C = (self.A >> 6) & 1;
+            self.V = ((self.A >> 6) ^ (self.A >> 5)) & 1;
+            return;
+        }
+        self.V = ((masked ^ self.A) >> 6) & 1;
+        if (masked & 0x0F) + (masked & 0x01) > 0x05 {
+            self.A = (self.A & 0xF0) | (self.A.wrapping_add(0x06) & 0x0F);
+        }
+        if (masked & 0xF0) as Word + (masked & 0x10) as Word > 0x50 {
+            self.A = self.A.wrapping_add(0x60);
+            self.C = 1;
+        } else {
+            self.C = 0;
+        }
+    }
+}
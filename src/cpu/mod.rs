@@ -1,14 +1,28 @@
-use crate::mem::Mem;
-use crate::opcodes::OpCode;
+use crate::mem::Bus;
+use crate::opcodes::{Instruction, OpCode};
 use crate::{Byte, Word};
 use std::fmt::{self, Debug};
 
+mod addressing;
+mod cmos;
+mod error;
+mod illegal;
+mod step;
+mod variant;
+mod xex;
+
+pub use error::CpuError;
+pub use illegal::{IllegalOpcodeAction, IllegalOpcodePolicy};
+pub use step::{Event, Interrupt, RunResult, StepResult};
+pub use variant::Variant;
+pub use xex::{XexError, XexInfo};
+
 #[allow(non_snake_case)] //disable snake case warning linting
 pub struct Cpu {
     /// Program Counter
     PC: Word,
-    /// stack pointer
-    SP: Word,
+    /// stack pointer, an offset into page $01
+    SP: Byte,
     /// Register A
     A: Byte,
     /// Register X
@@ -30,33 +44,59 @@ pub struct Cpu {
     V: Byte,
     /// status flag
     N: Byte,
+
+    /// which 6502 family member is being emulated
+    variant: Variant,
+
+    /// level of the IRQ line, `true` while asserted
+    irq_line: bool,
+    /// level of the NMI line, `true` while asserted
+    nmi_line: bool,
+    /// set on a falling edge of NMI until the interrupt is serviced
+    nmi_pending: bool,
+    /// set by `warm_reset` until the reset sequence has run
+    reset_pending: bool,
+
+    /// set by a JAM or STP opcode, cleared only by a reset
+    jammed: bool,
+    /// set by WAI until an interrupt line is asserted
+    waiting: bool,
+    /// set when the current instruction's indexing or branch crossed a page
+    page_crossed: bool,
+    /// cycles the current instruction takes beyond its table entry
+    extra_cycles: u32,
+    /// cycles taken since the CPU was created
+    total_cycles: u64,
+    /// chip-dependent constant ORed into A by the unstable ANE and LXA opcodes
+    unstable_magic: Byte,
+    /// what to do with opcodes outside the documented instruction set
+    illegal_opcode_policy: IllegalOpcodePolicy,
+
+    /// 6510 data direction register at $00, a set bit makes that pin an output
+    io_direction: Byte,
+    /// 6510 data register at $01, driven onto the output pins
+    io_data: Byte,
+    /// levels the outside world drives onto the 6510 input pins
+    io_input: Byte,
 }
 
-/// Writes a byte to the specified memory address and decrements the cycle count.
-///
-/// # Arguments
-///
-/// * `value` - The byte value to be written to memory.
-/// * `address` - The memory address where the byte will be written.
-/// * `cycles` - A mutable reference to the cycle count, which will be decremented by 1.
-/// * `memory` - A mutable reference to the memory where the byte will be written.
-///
-/// # Example
-///
-/// ```
-/// let mut cycles = 5;
-/// let mut memory = [0u8; 65536];
-/// write_byte(0xAB, 0x1234, &mut cycles, &mut memory);
-/// assert_eq!(memory[0x1234], 0xAB);
-/// assert_eq!(cycles, 4);
-/// ```
-fn write_byte(value: Byte, address: Word, cycles: &mut u32, memory: &mut Mem) {
-    memory[address.into()] = value;
-    *cycles -= 1;
+impl Default for Cpu {
+    fn default() -> Self {
+        Self::new(Variant::Nmos6502)
+    }
 }
 
 impl Cpu {
-    pub fn new() -> Cpu {
+    const STACK_PAGE: Word = 0x0100;
+    const NMI_VECTOR: Word = 0xFFFA;
+    const RESET_VECTOR: Word = 0xFFFC;
+    const IRQ_VECTOR: Word = 0xFFFE;
+    /// Reset, NMI, IRQ and BRK all take seven cycles.
+    const INTERRUPT_CYCLES: u32 = 7;
+    /// The NMOS JAM opcodes lock up after their two-cycle fetch.
+    const JAM_CYCLES: u32 = 2;
+
+    pub fn new(variant: Variant) -> Cpu {
         Cpu {
             PC: 0,
             SP: 0,
@@ -70,12 +110,93 @@ impl Cpu {
             B: 1,
             V: 1,
             N: 1,
+            variant,
+            irq_line: false,
+            nmi_line: false,
+            nmi_pending: false,
+            reset_pending: false,
+            jammed: false,
+            waiting: false,
+            page_crossed: false,
+            extra_cycles: 0,
+            total_cycles: 0,
+            unstable_magic: 0xEE,
+            illegal_opcode_policy: IllegalOpcodePolicy::default(),
+            io_direction: 0x00,
+            io_data: 0x00,
+            io_input: 0xFF,
         }
     }
 
-    pub fn reset(&mut self, memory: &mut Mem) {
-        self.PC = 0xFFFC;
-        self.SP = 0x0100;
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn pc(&self) -> Word {
+        self.PC
+    }
+
+    /// Moves PC, e.g. to the entry point of a program that was loaded after reset.
+    pub fn set_pc(&mut self, address: Word) {
+        self.PC = address;
+    }
+
+    /// Levels on the 6510 I/O port pins: output pins follow the data register and
+    /// input pins follow whatever was set with `set_io_port_input`.
+    pub fn io_port(&self) -> Byte {
+        (self.io_data & self.io_direction) | (self.io_input & !self.io_direction)
+    }
+
+    /// Drives the 6510 I/O port pins that are configured as inputs.
+    pub fn set_io_port_input(&mut self, value: Byte) {
+        self.io_input = value;
+    }
+
+    /// Whether a JAM or STP opcode has halted the CPU. Only a reset recovers from this.
+    pub fn is_jammed(&self) -> bool {
+        self.jammed
+    }
+
+    /// Sets the constant the unstable ANE and LXA opcodes OR into A. It varies between
+    /// chips and with temperature; common values are $00, $EE, $EF and $FF.
+    pub fn set_unstable_magic(&mut self, magic: Byte) {
+        self.unstable_magic = magic;
+    }
+
+    /// Sets what happens when an opcode outside the documented instruction set is
+    /// fetched.
+    pub fn set_illegal_opcode_policy(&mut self, policy: IllegalOpcodePolicy) {
+        self.illegal_opcode_policy = policy;
+    }
+
+    /// Pulls the IRQ line low. IRQ is level triggered: it is taken before every
+    /// instruction for as long as the line stays asserted and I is clear.
+    pub fn assert_irq(&mut self) {
+        self.irq_line = true;
+    }
+
+    pub fn release_irq(&mut self) {
+        self.irq_line = false;
+    }
+
+    /// Pulls the NMI line low. NMI is edge triggered: one interrupt is taken per
+    /// assertion, regardless of I, and the line must be released before it can fire again.
+    pub fn assert_nmi(&mut self) {
+        if !self.nmi_line {
+            self.nmi_pending = true;
+        }
+        self.nmi_line = true;
+    }
+
+    pub fn release_nmi(&mut self) {
+        self.nmi_line = false;
+    }
+
+    /// Power-on reset: clears the registers and runs the 7-cycle reset sequence, which
+    /// loads PC from the vector at $FFFC/$FFFD. Memory is left untouched, so programs
+    /// must be loaded before calling this.
+    pub fn reset<B: Bus>(&mut self, memory: &mut B) {
+        self.SP = 0x00;
         self.C = 0;
         self.Z = 0;
         self.I = 0;
@@ -87,38 +208,187 @@ impl Cpu {
         self.A = 0;
         self.X = 0;
         self.Y = 0;
-        memory.init();
+        self.irq_line = false;
+        self.nmi_line = false;
+        self.nmi_pending = false;
+        self.reset_pending = false;
+        self.io_direction = 0x00;
+        self.io_data = 0x00;
+
+        self.reset_sequence(memory);
+        self.total_cycles += Self::INTERRUPT_CYCLES as u64;
+    }
+
+    /// Warm reset: the reset sequence runs before the next instruction `step` would
+    /// fetch. Unlike `reset`, A, X, Y and the flags other than I keep their values.
+    pub fn warm_reset(&mut self) {
+        self.reset_pending = true;
     }
 
-    fn fetch_byte(&mut self, cycles: &mut u32, memory: &mut Mem) -> Byte {
-        let data: Byte = memory[self.PC];
-        self.PC += 1;
-        *cycles -= 1;
+    /// Reads a byte from the bus. On the 6510, $00 and $01 are the on-chip I/O port
+    /// rather than memory.
+    fn load<B: Bus>(&self, address: Word, memory: &mut B) -> Byte {
+        if self.variant.has_io_port() {
+            match address {
+                0x0000 => return self.io_direction,
+                0x0001 => return self.io_port(),
+                _ => {}
+            }
+        }
+        memory.read(address)
+    }
+
+    /// Writes a byte to the specified memory address.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The byte value to be written to memory.
+    /// * `address` - The memory address where the byte will be written.
+    /// * `memory` - The bus the byte is written to.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut memory = Mem::new();
+    /// cpu.write_byte(0xAB, 0x1234, &mut memory);
+    /// assert_eq!(memory[0x1234], 0xAB);
+    /// ```
+    fn write_byte<B: Bus>(&mut self, value: Byte, address: Word, memory: &mut B) {
+        if self.variant.has_io_port() {
+            // The port registers sit on top of RAM, which still sees the write.
+            match address {
+                0x0000 => self.io_direction = value,
+                0x0001 => self.io_data = value,
+                _ => {}
+            }
+        }
+        memory.write(address, value);
+    }
+
+    fn fetch_byte<B: Bus>(&mut self, memory: &mut B) -> Byte {
+        let data: Byte = self.load(self.PC, memory);
+        self.PC = self.PC.wrapping_add(1);
         data
     }
 
-    fn fetch_word(&mut self, cycles: &mut u32, memory: &mut Mem) -> Word {
-        let mut data: Word = memory[self.PC] as Word;
-        self.PC += 1;
-        data |= (memory[self.PC] as Word) << 8;
-        self.PC += 1;
-        *cycles -= 2;
+    fn fetch_word<B: Bus>(&mut self, memory: &mut B) -> Word {
+        let mut data: Word = self.load(self.PC, memory) as Word;
+        self.PC = self.PC.wrapping_add(1);
+        data |= (self.load(self.PC, memory) as Word) << 8;
+        self.PC = self.PC.wrapping_add(1);
         data
     }
 
-    fn read_byte(&mut self, address: Word, cycles: &mut u32, memory: &mut Mem) -> Byte {
-        let data: Byte = memory[address.into()];
-        *cycles -= 1;
+    fn read_byte<B: Bus>(&mut self, address: Word, memory: &mut B) -> Byte {
+        let data: Byte = self.load(address, memory);
         data
     }
 
-    fn read_word(&mut self, address: Word, cycles: &mut u32, memory: &mut Mem) -> Word {
-        let low_byte = self.read_byte(address, cycles, memory) as Word;
-        let high_byte = self.read_byte(address + 1, cycles, memory) as Word;
+    fn read_word<B: Bus>(&mut self, address: Word, memory: &mut B) -> Word {
+        let low_byte = self.read_byte(address, memory) as Word;
+        let high_byte = self.read_byte(address.wrapping_add(1), memory) as Word;
         let data: Word = (high_byte << 8) | low_byte;
         data
     }
 
+    /// Writes `value` to the top of the stack and moves SP down, wrapping within page $01.
+    fn push_byte<B: Bus>(&mut self, value: Byte, memory: &mut B) {
+        self.write_byte(value, Self::STACK_PAGE | self.SP as Word, memory);
+        self.SP = self.SP.wrapping_sub(1);
+    }
+
+    /// Moves SP up, wrapping within page $01, and reads the byte it now points at.
+    fn pull_byte<B: Bus>(&mut self, memory: &mut B) -> Byte {
+        self.SP = self.SP.wrapping_add(1);
+        self.read_byte(Self::STACK_PAGE | self.SP as Word, memory)
+    }
+
+    /// Pushes the high byte first, so the word sits little-endian on the stack.
+    fn push_word<B: Bus>(&mut self, value: Word, memory: &mut B) {
+        self.push_byte((value >> 8) as Byte, memory);
+        self.push_byte((value & 0xFF) as Byte, memory);
+    }
+
+    fn pull_word<B: Bus>(&mut self, memory: &mut B) -> Word {
+        let low_byte = self.pull_byte(memory) as Word;
+        let high_byte = self.pull_byte(memory) as Word;
+        (high_byte << 8) | low_byte
+    }
+
+    /// Packs the flags into the P register layout `NV1BDIZC`. B only exists on the
+    /// stack: it is set when P is pushed by PHP or BRK and clear for IRQ and NMI.
+    fn status(&self, break_flag: bool) -> Byte {
+        (self.N << 7)
+            | (self.V << 6)
+            | 0b0010_0000
+            | (if break_flag { 1 } else { 0 } << 4)
+            | (self.D << 3)
+            | (self.I << 2)
+            | (self.Z << 1)
+            | self.C
+    }
+
+    /// Unpacks a P register value into the flags. Bits 4 and 5 are ignored.
+    fn set_status(&mut self, value: Byte) {
+        self.N = (value >> 7) & 1;
+        self.V = (value >> 6) & 1;
+        self.D = (value >> 3) & 1;
+        self.I = (value >> 2) & 1;
+        self.Z = (value >> 1) & 1;
+        self.C = value & 1;
+    }
+
+    /// Pushes PC and P, sets I and loads PC from `vector`.
+    fn interrupt<B: Bus>(&mut self, vector: Word, break_flag: bool, memory: &mut B) {
+        self.push_word(self.PC, memory);
+        self.push_byte(self.status(break_flag), memory);
+        self.I = 1;
+        self.waiting = false;
+        if self.variant.clears_decimal_on_interrupt() {
+            self.D = 0;
+        }
+        self.PC = self.read_word(vector, memory);
+    }
+
+    /// Like an interrupt, but the three stack accesses are reads, so SP moves down
+    /// without anything being written.
+    fn reset_sequence<B: Bus>(&mut self, memory: &mut B) {
+        for _ in 0..3 {
+            self.SP = self.SP.wrapping_sub(1);
+        }
+        self.I = 1;
+        if self.variant.clears_decimal_on_interrupt() {
+            self.D = 0;
+        }
+        self.jammed = false;
+        self.waiting = false;
+        self.PC = self.read_word(Self::RESET_VECTOR, memory);
+    }
+
+    /// Runs a pending reset, then services a pending NMI, or an IRQ if the line is
+    /// asserted and I is clear. Returns the one that was taken; each takes
+    /// `INTERRUPT_CYCLES`.
+    fn poll_interrupts<B: Bus>(&mut self, memory: &mut B) -> Option<Interrupt> {
+        if self.reset_pending {
+            self.reset_pending = false;
+            self.reset_sequence(memory);
+            return Some(Interrupt::Reset);
+        }
+        if self.jammed {
+            return None;
+        }
+        let (interrupt, vector) = if self.nmi_pending {
+            self.nmi_pending = false;
+            (Interrupt::Nmi, Self::NMI_VECTOR)
+        } else if self.irq_line && self.I == 0 {
+            (Interrupt::Irq, Self::IRQ_VECTOR)
+        } else {
+            return None;
+        };
+        self.interrupt(vector, false, memory);
+        Some(interrupt)
+    }
+
     #[allow(non_snake_case)]
     fn LDASetStatus(&mut self) {
         self.Z = if self.A == 0 { 1 } else { 0 };
@@ -128,226 +398,587 @@ impl Cpu {
     #[allow(non_snake_case)]
     fn LDXSetStatus(&mut self) {
         self.Z = if self.X == 0 { 1 } else { 0 };
-        self.N = if self.A & 0b10000000 != 0 { 1 } else { 0 };
+        self.N = if self.X & 0b10000000 != 0 { 1 } else { 0 };
     }
 
     #[allow(non_snake_case)]
     fn LDYSetStatus(&mut self) {
         self.Z = if self.Y == 0 { 1 } else { 0 };
-        self.N = if self.A & 0b10000000 != 0 { 1 } else { 0 };
+        self.N = if self.Y & 0b10000000 != 0 { 1 } else { 0 };
     }
 
-    pub fn execute(&mut self, cycles: &mut u32, memory: &mut Mem) {
-        while *cycles > 0 {
-            let instruction: Byte = self.fetch_byte(cycles, memory);
-            match instruction {
-                OpCode::INS_LDA_IM => {
-                    let value: Byte = self.fetch_byte(cycles, memory);
-                    self.A = value;
-                    self.LDASetStatus();
-                }
-                OpCode::INS_LDA_ZP => {
-                    let zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    self.A = self.read_byte(zero_page_address.into(), cycles, memory);
-                    self.LDASetStatus();
-                }
-                OpCode::INS_LDA_ZPX => {
-                    let mut zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    zero_page_address += self.X;
-                    *cycles -= 1;
-                    self.A = self.read_byte(zero_page_address.into(), cycles, memory);
-                    self.LDASetStatus();
-                }
-                OpCode::INS_JSR => {
-                    let jmp_address: Word = self.fetch_word(cycles, memory);
-                    memory.wtire_word(self.PC - 1, self.SP, cycles);
-                    self.SP += 2;
-                    self.PC = jmp_address;
-                    *cycles -= 1;
-                }
-                OpCode::INS_LDA_ABS => {
-                    let abs_address: Word = self.fetch_word(cycles, memory);
-                    self.A = self.read_byte(abs_address, cycles, memory);
-                    self.LDASetStatus();
-                }
-                OpCode::INS_LDA_ABSX => {
-                    let abs_address: Word = self.fetch_word(cycles, memory);
-                    let abs_address_x: Word = abs_address + self.X as Word;
-                    if abs_address_x - abs_address >= 0xFF {
-                        *cycles -= 1;
-                    }
-                    self.A = self.read_byte(abs_address_x, cycles, memory);
-                    self.LDASetStatus();
-                }
-                OpCode::INS_LDA_ABSY => {
-                    let abs_address: Word = self.fetch_word(cycles, memory);
-                    let abs_address_y: Word = abs_address + self.Y as Word;
-                    self.A = self.read_byte(abs_address_y, cycles, memory);
-                    if abs_address_y - abs_address >= 0xFF {
-                        *cycles -= 1;
-                    }
-                    self.LDASetStatus();
-                }
-                OpCode::INS_LDA_INDX => {
-                    let zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    let zero_page_address_x: Byte = zero_page_address + self.X;
-                    *cycles -= 1;
-                    let effective_address: Word =
-                        self.read_word(zero_page_address_x.into(), cycles, memory);
-                    self.A = self.read_byte(effective_address, cycles, memory);
-                    self.LDASetStatus();
-                }
-                OpCode::INS_LDA_INDY => {
-                    let zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    let effective_address: Word =
-                        self.read_word(zero_page_address.into(), cycles, memory);
-                    let effective_address_y: Word = effective_address + self.Y as Word;
-                    self.A = self.read_byte(effective_address_y, cycles, memory);
-                    if effective_address_y - effective_address >= 0xFF {
-                        *cycles -= 1;
-                    }
-                    self.LDASetStatus();
-                }
-                OpCode::INS_LDY_ABS => {
-                    let abs_address: Word = self.fetch_word(cycles, memory);
-                    self.Y = self.read_byte(abs_address, cycles, memory);
-                    self.LDYSetStatus();
-                }
-                OpCode::INS_LDX_ABS => {
-                    let abs_address: Word = self.fetch_word(cycles, memory);
-                    self.X = self.read_byte(abs_address, cycles, memory);
-                    self.LDXSetStatus();
-                }
-                OpCode::INS_LDX_ABSY => {
-                    let abs_address: Word = self.fetch_word(cycles, memory);
-                    let abs_address_x: Word = abs_address + self.Y as Word;
-                    if abs_address_x - abs_address >= 0xFF {
-                        *cycles -= 1;
-                    }
-                    self.X = self.read_byte(abs_address_x, cycles, memory);
-                    self.LDXSetStatus();
-                }
-                OpCode::INS_LDX_ZP => {
-                    let zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    self.X = self.read_byte(zero_page_address.into(), cycles, memory);
-                    self.LDASetStatus();
-                }
-                OpCode::INS_LDX_ZPY => {
-                    let mut zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    zero_page_address += self.Y;
-                    *cycles -= 1;
-                    self.X = self.read_byte(zero_page_address.into(), cycles, memory);
-                    self.LDXSetStatus();
-                }
-                OpCode::INS_LDY_ABSX => {
-                    let abs_address: Word = self.fetch_word(cycles, memory);
-                    let abs_address_x: Word = abs_address + self.X as Word;
-                    if abs_address_x - abs_address >= 0xFF {
-                        *cycles -= 1;
-                    }
-                    self.Y = self.read_byte(abs_address_x, cycles, memory);
-                    self.LDYSetStatus();
-                }
-                OpCode::INS_LDY_ZP => {
-                    let zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    self.Y = self.read_byte(zero_page_address.into(), cycles, memory);
-                    self.LDYSetStatus();
-                }
-                OpCode::INS_LDY_ZPX => {
-                    let mut zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    zero_page_address += self.X;
-                    *cycles -= 1;
-                    self.Y = self.read_byte(zero_page_address.into(), cycles, memory);
-                    self.LDYSetStatus();
-                }
+    /// Whether ADC and SBC should work in BCD. The 2A03 ignores the D flag.
+    fn decimal_mode(&self) -> bool {
+        self.D != 0 && self.variant.has_decimal_mode()
+    }
 
-                OpCode::INS_LDX_IM => {
-                    let value: Byte = self.fetch_byte(cycles, memory);
-                    self.X = value;
-                    self.LDXSetStatus();
-                }
+    /// Adds `value` and the carry to A. With D set the operands are treated as packed
+    /// BCD; on NMOS parts Z then reflects the binary sum while N and V are taken from
+    /// the intermediate result before the high nibble is adjusted. The 65C02 sets N
+    /// and Z from the BCD result.
+    fn adc(&mut self, value: Byte) {
+        let a = self.A as Word;
+        let m = value as Word;
+        let carry = self.C as Word;
+        let binary = a + m + carry;
 
-                OpCode::INS_LDY_IM => {
-                    let value: Byte = self.fetch_byte(cycles, memory);
-                    self.Y = value;
-                    self.LDYSetStatus();
-                }
+        if !self.decimal_mode() {
+            self.C = if binary > 0xFF { 1 } else { 0 };
+            self.V = if !(a ^ m) & (a ^ binary) & 0x80 != 0 {
+                1
+            } else {
+                0
+            };
+            self.A = binary as Byte;
+            self.LDASetStatus();
+            return;
+        }
 
-                OpCode::INS_STA_ZP => {
-                    let zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    write_byte(self.A, zero_page_address.into(), cycles, memory)
-                }
-                OpCode::INS_STA_ZPX => {
-                    let mut zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    zero_page_address += self.X;
-                    *cycles -= 1;
-                    write_byte(self.A, zero_page_address.into(), cycles, memory)
-                }
-                OpCode::INS_STA_ABS => {
-                    let abs_address: Word = self.fetch_word(cycles, memory);
-                    write_byte(self.A, abs_address, cycles, memory)
-                }
-                OpCode::INS_STA_ABSX => {
-                    let abs_address: Word = self.fetch_word(cycles, memory);
-                    let abs_address_x: Word = abs_address + self.X as Word;
-                    *cycles -= 1;
-                    write_byte(self.A, abs_address_x, cycles, memory);
-                }
-                OpCode::INS_STA_ABSY => {
-                    let abs_address: Word = self.fetch_word(cycles, memory);
-                    let abs_address_y: Word = abs_address + self.Y as Word;
-                    *cycles -= 1;
-                    write_byte(self.A, abs_address_y, cycles, memory);
-                }
-                OpCode::INS_STA_INDX => {
-                    let zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    let zero_page_address_x: Byte = zero_page_address + self.X;
-                    *cycles -= 1;
-                    let effective_address: Word =
-                        self.read_word(zero_page_address_x.into(), cycles, memory);
-                    write_byte(self.A, effective_address, cycles, memory);
-                }
-                OpCode::INS_STA_INDY => {
-                    let zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    let effective_address: Word =
-                        self.read_word(zero_page_address.into(), cycles, memory);
-                    let effective_address_y: Word = effective_address + self.Y as Word;
-                    *cycles -= 1;
-                    write_byte(self.A, effective_address_y, cycles, memory);
-                }
-                OpCode::INS_STX_ZP => {
-                    let zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    write_byte(self.X, zero_page_address.into(), cycles, memory)
-                }
-                OpCode::INS_STX_ZPY => {
-                    let mut zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    zero_page_address += self.Y;
-                    *cycles -= 1;
-                    write_byte(self.X, zero_page_address.into(), cycles, memory)
-                }
-                OpCode::INS_STY_ZP => {
-                    let zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    write_byte(self.Y, zero_page_address.into(), cycles, memory)
-                }
-                OpCode::INS_STY_ZPX => {
-                    let mut zero_page_address: Byte = self.fetch_byte(cycles, memory);
-                    zero_page_address += self.X;
-                    *cycles -= 1;
-                    write_byte(self.Y, zero_page_address.into(), cycles, memory)
-                }
-                OpCode::INS_STX_ABS => {
-                    let zero_page_address: Word = self.fetch_word(cycles, memory);
-                    write_byte(self.X, zero_page_address, cycles, memory)
+        let mut low = (a & 0x0F) + (m & 0x0F) + carry;
+        if low > 0x09 {
+            low += 0x06;
+        }
+        let mut high = (a >> 4) + (m >> 4) + if low > 0x0F { 1 } else { 0 };
+        self.Z = if binary & 0xFF == 0 { 1 } else { 0 };
+        self.N = if high & 0x08 != 0 { 1 } else { 0 };
+        self.V = if !(a ^ m) & (a ^ (high << 4)) & 0x80 != 0 {
+            1
+        } else {
+            0
+        };
+        if high > 0x09 {
+            high += 0x06;
+        }
+        self.C = if high > 0x0F { 1 } else { 0 };
+        self.A = ((high << 4) | (low & 0x0F)) as Byte;
+        if self.variant.is_cmos() {
+            // The 65C02 spends an extra cycle fixing up the flags.
+            self.extra_cycles += 1;
+            self.LDASetStatus();
+        }
+    }
+
+    /// Subtracts `value` and the borrow (inverted carry) from A. With D set the result
+    /// is BCD adjusted; on NMOS parts all flags reflect the binary difference, while
+    /// the 65C02 sets N and Z from the BCD result.
+    fn sbc(&mut self, value: Byte) {
+        let a = self.A as Word;
+        let m = value as Word;
+        let borrow = 1 - self.C as Word;
+        let binary = a.wrapping_sub(m).wrapping_sub(borrow);
+
+        let decimal = if self.decimal_mode() && self.variant.is_cmos() {
+            // The 65C02 corrects the whole difference rather than each nibble, which
+            // only makes a difference for operands that are not valid BCD.
+            let low = (a & 0x0F) as i16 - (m & 0x0F) as i16 - borrow as i16;
+            let mut result = a as i16 - m as i16 - borrow as i16;
+            if result < 0 {
+                result -= 0x60;
+            }
+            if low < 0 {
+                result -= 0x06;
+            }
+            Some((result & 0xFF) as Byte)
+        } else if self.decimal_mode() {
+            let mut low = (a & 0x0F) as i16 - (m & 0x0F) as i16 - borrow as i16;
+            let mut high = (a >> 4) as i16 - (m >> 4) as i16;
+            if low < 0 {
+                low -= 0x06;
+                high -= 1;
+            }
+            if high < 0 {
+                high -= 0x06;
+            }
+            Some((((high << 4) | (low & 0x0F)) & 0xFF) as Byte)
+        } else {
+            None
+        };
+
+        self.C = if binary < 0x100 { 1 } else { 0 };
+        self.V = if (a ^ m) & (a ^ binary) & 0x80 != 0 {
+            1
+        } else {
+            0
+        };
+        self.A = binary as Byte;
+        self.LDASetStatus();
+        if let Some(result) = decimal {
+            self.A = result;
+            if self.variant.is_cmos() {
+                self.extra_cycles += 1;
+                self.LDASetStatus();
+            }
+        }
+    }
+
+    fn and(&mut self, value: Byte) {
+        self.A &= value;
+        self.LDASetStatus();
+    }
+
+    fn ora(&mut self, value: Byte) {
+        self.A |= value;
+        self.LDASetStatus();
+    }
+
+    fn eor(&mut self, value: Byte) {
+        self.A ^= value;
+        self.LDASetStatus();
+    }
+
+    /// Z is set from `A & value`, while N and V are copied from bits 7 and 6 of `value`.
+    fn bit(&mut self, value: Byte) {
+        self.Z = if self.A & value == 0 { 1 } else { 0 };
+        self.N = (value >> 7) & 1;
+        self.V = (value >> 6) & 1;
+    }
+
+    /// Runs `operation` on the byte at `address`. NMOS parts write the unmodified value
+    /// back once before the result, which memory-mapped I/O can observe; the 65C02
+    /// reads it again instead.
+    fn read_modify_write<B: Bus>(
+        &mut self,
+        address: Word,
+        operation: fn(&mut Self, Byte) -> Byte,
+        memory: &mut B,
+    ) {
+        let value: Byte = self.read_byte(address, memory);
+        if self.variant.has_rmw_dummy_write() {
+            self.write_byte(value, address, memory);
+        } else {
+            self.read_byte(address, memory);
+        }
+        let result: Byte = operation(self, value);
+        self.write_byte(result, address, memory);
+    }
+
+    /// Sets Z and N from `value` and returns it.
+    fn set_zero_negative(&mut self, value: Byte) -> Byte {
+        self.Z = if value == 0 { 1 } else { 0 };
+        self.N = if value & 0b10000000 != 0 { 1 } else { 0 };
+        value
+    }
+
+    fn asl(&mut self, value: Byte) -> Byte {
+        self.C = value >> 7;
+        self.set_zero_negative(value << 1)
+    }
+
+    fn lsr(&mut self, value: Byte) -> Byte {
+        self.C = value & 1;
+        self.set_zero_negative(value >> 1)
+    }
+
+    fn rol(&mut self, value: Byte) -> Byte {
+        let carry_in: Byte = self.C;
+        self.C = value >> 7;
+        self.set_zero_negative((value << 1) | carry_in)
+    }
+
+    fn ror(&mut self, value: Byte) -> Byte {
+        let carry_in: Byte = self.C;
+        self.C = value & 1;
+        self.set_zero_negative((value >> 1) | (carry_in << 7))
+    }
+
+    fn inc(&mut self, value: Byte) -> Byte {
+        self.set_zero_negative(value.wrapping_add(1))
+    }
+
+    fn dec(&mut self, value: Byte) -> Byte {
+        self.set_zero_negative(value.wrapping_sub(1))
+    }
+
+    /// Sets C if `register >= value`, and Z and N from `register - value`.
+    fn compare(&mut self, register: Byte, value: Byte) {
+        self.C = if register >= value { 1 } else { 0 };
+        self.set_zero_negative(register.wrapping_sub(value));
+    }
+
+    fn compare_a(&mut self, value: Byte) {
+        self.compare(self.A, value);
+    }
+
+    fn compare_x(&mut self, value: Byte) {
+        self.compare(self.X, value);
+    }
+
+    fn compare_y(&mut self, value: Byte) {
+        self.compare(self.Y, value);
+    }
+
+    /// Jumps to `target`, resolved from the branch offset, if `condition` holds. A
+    /// taken branch costs one extra cycle, and one more if the target is on a
+    /// different page.
+    fn branch(&mut self, condition: bool, target: Word) {
+        if !condition {
+            self.page_crossed = false;
+            return;
+        }
+        self.extra_cycles += 1;
+        self.PC = target;
+    }
+
+    /// Executes one instruction, or runs one interrupt sequence if an interrupt is
+    /// pending. A halted or waiting CPU spends a single cycle doing nothing. Fails only
+    /// if an illegal opcode is fetched under `IllegalOpcodePolicy::Halt`.
+    pub fn step<B: Bus>(&mut self, memory: &mut B) -> Result<StepResult, CpuError> {
+        if let Some(interrupt) = self.poll_interrupts(memory) {
+            return Ok(self.finish_step(
+                None,
+                Self::INTERRUPT_CYCLES,
+                Some(Event::Interrupt(interrupt)),
+            ));
+        }
+        if self.jammed {
+            // A jammed CPU ignores interrupts and waits for a reset.
+            return Ok(self.finish_step(None, 1, Some(Event::Halted)));
+        }
+        if self.waiting {
+            // WAI resumes on any interrupt line, even an IRQ masked by I.
+            if !self.irq_line {
+                return Ok(self.finish_step(None, 1, None));
+            }
+            self.waiting = false;
+        }
+        let pc: Word = self.PC;
+        let instruction: Byte = self.fetch_byte(memory);
+        let info: &Instruction = OpCode::decode(self.variant, instruction);
+        if !OpCode::is_documented(self.variant, instruction) {
+            match self.illegal_opcode_action(pc, instruction) {
+                IllegalOpcodeAction::Execute => {}
+                IllegalOpcodeAction::Halt => {
+                    self.PC = pc;
+                    return Err(CpuError::IllegalOpcode {
+                        pc,
+                        opcode: instruction,
+                    });
                 }
-                OpCode::INS_STY_ABS => {
-                    let zero_page_address: Word = self.fetch_word(cycles, memory);
-                    write_byte(self.Y, zero_page_address, cycles, memory)
+                IllegalOpcodeAction::Nop => {
+                    self.resolve_address(info.mode, memory);
+                    return Ok(self.finish_step(
+                        Some(instruction),
+                        info.cycles.into(),
+                        Some(Event::IllegalOpcode),
+                    ));
                 }
-                _ => {
-                    println!("Instruction not handled {}", instruction);
+                IllegalOpcodeAction::Jam => {
+                    self.jammed = true;
+                    return Ok(self.finish_step(
+                        Some(instruction),
+                        Self::JAM_CYCLES,
+                        Some(Event::Halted),
+                    ));
                 }
             }
         }
+        let (address, page_crossed) = self.resolve_address(info.mode, memory);
+        self.page_crossed = page_crossed;
+        self.extra_cycles = 0;
+        match instruction {
+            OpCode::INS_LDA_IM
+            | OpCode::INS_LDA_ZP
+            | OpCode::INS_LDA_ZPX
+            | OpCode::INS_LDA_ABS
+            | OpCode::INS_LDA_ABSX
+            | OpCode::INS_LDA_ABSY
+            | OpCode::INS_LDA_INDX
+            | OpCode::INS_LDA_INDY => {
+                self.A = self.read_byte(address, memory);
+                self.LDASetStatus();
+            }
+            OpCode::INS_LDX_IM
+            | OpCode::INS_LDX_ZP
+            | OpCode::INS_LDX_ZPY
+            | OpCode::INS_LDX_ABS
+            | OpCode::INS_LDX_ABSY => {
+                self.X = self.read_byte(address, memory);
+                self.LDXSetStatus();
+            }
+            OpCode::INS_LDY_IM
+            | OpCode::INS_LDY_ZP
+            | OpCode::INS_LDY_ZPX
+            | OpCode::INS_LDY_ABS
+            | OpCode::INS_LDY_ABSX => {
+                self.Y = self.read_byte(address, memory);
+                self.LDYSetStatus();
+            }
+            OpCode::INS_STA_ZP
+            | OpCode::INS_STA_ZPX
+            | OpCode::INS_STA_ABS
+            | OpCode::INS_STA_ABSX
+            | OpCode::INS_STA_ABSY
+            | OpCode::INS_STA_INDX
+            | OpCode::INS_STA_INDY => {
+                self.write_byte(self.A, address, memory);
+            }
+            OpCode::INS_STX_ZP | OpCode::INS_STX_ZPY | OpCode::INS_STX_ABS => {
+                self.write_byte(self.X, address, memory);
+            }
+            OpCode::INS_STY_ZP | OpCode::INS_STY_ZPX | OpCode::INS_STY_ABS => {
+                self.write_byte(self.Y, address, memory);
+            }
+            OpCode::INS_ADC_IM
+            | OpCode::INS_ADC_ZP
+            | OpCode::INS_ADC_ZPX
+            | OpCode::INS_ADC_ABS
+            | OpCode::INS_ADC_ABSX
+            | OpCode::INS_ADC_ABSY
+            | OpCode::INS_ADC_INDX
+            | OpCode::INS_ADC_INDY => {
+                let value: Byte = self.read_byte(address, memory);
+                self.adc(value);
+            }
+            OpCode::INS_SBC_IM
+            | OpCode::INS_SBC_ZP
+            | OpCode::INS_SBC_ZPX
+            | OpCode::INS_SBC_ABS
+            | OpCode::INS_SBC_ABSX
+            | OpCode::INS_SBC_ABSY
+            | OpCode::INS_SBC_INDX
+            | OpCode::INS_SBC_INDY => {
+                let value: Byte = self.read_byte(address, memory);
+                self.sbc(value);
+            }
+            OpCode::INS_AND_IM
+            | OpCode::INS_AND_ZP
+            | OpCode::INS_AND_ZPX
+            | OpCode::INS_AND_ABS
+            | OpCode::INS_AND_ABSX
+            | OpCode::INS_AND_ABSY
+            | OpCode::INS_AND_INDX
+            | OpCode::INS_AND_INDY => {
+                let value: Byte = self.read_byte(address, memory);
+                self.and(value);
+            }
+            OpCode::INS_ORA_IM
+            | OpCode::INS_ORA_ZP
+            | OpCode::INS_ORA_ZPX
+            | OpCode::INS_ORA_ABS
+            | OpCode::INS_ORA_ABSX
+            | OpCode::INS_ORA_ABSY
+            | OpCode::INS_ORA_INDX
+            | OpCode::INS_ORA_INDY => {
+                let value: Byte = self.read_byte(address, memory);
+                self.ora(value);
+            }
+            OpCode::INS_EOR_IM
+            | OpCode::INS_EOR_ZP
+            | OpCode::INS_EOR_ZPX
+            | OpCode::INS_EOR_ABS
+            | OpCode::INS_EOR_ABSX
+            | OpCode::INS_EOR_ABSY
+            | OpCode::INS_EOR_INDX
+            | OpCode::INS_EOR_INDY => {
+                let value: Byte = self.read_byte(address, memory);
+                self.eor(value);
+            }
+            OpCode::INS_BIT_ZP | OpCode::INS_BIT_ABS => {
+                let value: Byte = self.read_byte(address, memory);
+                self.bit(value);
+            }
+            OpCode::INS_ASL_ACC => {
+                self.A = self.asl(self.A);
+            }
+            OpCode::INS_ASL_ZP
+            | OpCode::INS_ASL_ZPX
+            | OpCode::INS_ASL_ABS
+            | OpCode::INS_ASL_ABSX => {
+                self.read_modify_write(address, Self::asl, memory);
+            }
+            OpCode::INS_LSR_ACC => {
+                self.A = self.lsr(self.A);
+            }
+            OpCode::INS_LSR_ZP
+            | OpCode::INS_LSR_ZPX
+            | OpCode::INS_LSR_ABS
+            | OpCode::INS_LSR_ABSX => {
+                self.read_modify_write(address, Self::lsr, memory);
+            }
+            OpCode::INS_ROL_ACC => {
+                self.A = self.rol(self.A);
+            }
+            OpCode::INS_ROL_ZP
+            | OpCode::INS_ROL_ZPX
+            | OpCode::INS_ROL_ABS
+            | OpCode::INS_ROL_ABSX => {
+                self.read_modify_write(address, Self::rol, memory);
+            }
+            OpCode::INS_ROR_ACC => {
+                self.A = self.ror(self.A);
+            }
+            OpCode::INS_ROR_ZP
+            | OpCode::INS_ROR_ZPX
+            | OpCode::INS_ROR_ABS
+            | OpCode::INS_ROR_ABSX => {
+                self.read_modify_write(address, Self::ror, memory);
+            }
+            OpCode::INS_INC_ZP
+            | OpCode::INS_INC_ZPX
+            | OpCode::INS_INC_ABS
+            | OpCode::INS_INC_ABSX => {
+                self.read_modify_write(address, Self::inc, memory);
+            }
+            OpCode::INS_DEC_ZP
+            | OpCode::INS_DEC_ZPX
+            | OpCode::INS_DEC_ABS
+            | OpCode::INS_DEC_ABSX => {
+                self.read_modify_write(address, Self::dec, memory);
+            }
+            OpCode::INS_INX => {
+                self.X = self.inc(self.X);
+            }
+            OpCode::INS_INY => {
+                self.Y = self.inc(self.Y);
+            }
+            OpCode::INS_DEX => {
+                self.X = self.dec(self.X);
+            }
+            OpCode::INS_DEY => {
+                self.Y = self.dec(self.Y);
+            }
+            OpCode::INS_CMP_IM
+            | OpCode::INS_CMP_ZP
+            | OpCode::INS_CMP_ZPX
+            | OpCode::INS_CMP_ABS
+            | OpCode::INS_CMP_ABSX
+            | OpCode::INS_CMP_ABSY
+            | OpCode::INS_CMP_INDX
+            | OpCode::INS_CMP_INDY => {
+                let value: Byte = self.read_byte(address, memory);
+                self.compare_a(value);
+            }
+            OpCode::INS_CPX_IM | OpCode::INS_CPX_ZP | OpCode::INS_CPX_ABS => {
+                let value: Byte = self.read_byte(address, memory);
+                self.compare_x(value);
+            }
+            OpCode::INS_CPY_IM | OpCode::INS_CPY_ZP | OpCode::INS_CPY_ABS => {
+                let value: Byte = self.read_byte(address, memory);
+                self.compare_y(value);
+            }
+            OpCode::INS_BCC => {
+                self.branch(self.C == 0, address);
+            }
+            OpCode::INS_BCS => {
+                self.branch(self.C != 0, address);
+            }
+            OpCode::INS_BEQ => {
+                self.branch(self.Z != 0, address);
+            }
+            OpCode::INS_BMI => {
+                self.branch(self.N != 0, address);
+            }
+            OpCode::INS_BNE => {
+                self.branch(self.Z == 0, address);
+            }
+            OpCode::INS_BPL => {
+                self.branch(self.N == 0, address);
+            }
+            OpCode::INS_BVC => {
+                self.branch(self.V == 0, address);
+            }
+            OpCode::INS_BVS => {
+                self.branch(self.V != 0, address);
+            }
+            OpCode::INS_PHA => {
+                self.push_byte(self.A, memory);
+            }
+            OpCode::INS_PHP => {
+                self.push_byte(self.status(true), memory);
+            }
+            OpCode::INS_PLA => {
+                self.A = self.pull_byte(memory);
+                self.LDASetStatus();
+            }
+            OpCode::INS_PLP => {
+                let status: Byte = self.pull_byte(memory);
+                self.set_status(status);
+            }
+            OpCode::INS_TSX => {
+                self.X = self.SP;
+                self.LDXSetStatus();
+            }
+            OpCode::INS_TXS => {
+                self.SP = self.X;
+            }
+            OpCode::INS_JSR => {
+                self.push_word(self.PC.wrapping_sub(1), memory);
+                self.PC = address;
+            }
+            OpCode::INS_JMP_ABS | OpCode::INS_JMP_IND => {
+                self.PC = address;
+            }
+            OpCode::INS_RTS => {
+                let return_address: Word = self.pull_word(memory);
+                self.PC = return_address.wrapping_add(1);
+            }
+            OpCode::INS_BRK => {
+                // BRK is followed by a padding byte that the return address skips.
+                self.fetch_byte(memory);
+                self.interrupt(Self::IRQ_VECTOR, true, memory);
+            }
+            OpCode::INS_RTI => {
+                let status: Byte = self.pull_byte(memory);
+                self.set_status(status);
+                self.PC = self.pull_word(memory);
+            }
+            OpCode::INS_TAX => {
+                self.X = self.A;
+                self.LDXSetStatus();
+            }
+            OpCode::INS_TAY => {
+                self.Y = self.A;
+                self.LDYSetStatus();
+            }
+            OpCode::INS_TXA => {
+                self.A = self.X;
+                self.LDASetStatus();
+            }
+            OpCode::INS_TYA => {
+                self.A = self.Y;
+                self.LDASetStatus();
+            }
+            OpCode::INS_CLC => {
+                self.C = 0;
+            }
+            OpCode::INS_SEC => {
+                self.C = 1;
+            }
+            OpCode::INS_CLI => {
+                self.I = 0;
+            }
+            OpCode::INS_SEI => {
+                self.I = 1;
+            }
+            OpCode::INS_CLD => {
+                self.D = 0;
+            }
+            OpCode::INS_SED => {
+                self.D = 1;
+            }
+            OpCode::INS_CLV => {
+                self.V = 0;
+            }
+            OpCode::INS_NOP => {}
+            _ if self.variant.has_illegal_opcodes() => {
+                self.execute_illegal(instruction, address, memory);
+            }
+            _ => {
+                self.execute_cmos(instruction, address, memory);
+            }
+        }
+        let mut cycles: u32 = info.cycles as u32 + self.extra_cycles;
+        if self.page_crossed && info.page_cross_penalty {
+            cycles += 1;
+        }
+        let event: Option<Event> = if self.jammed {
+            Some(Event::Halted)
+        } else if !OpCode::is_documented(self.variant, instruction) {
+            Some(Event::IllegalOpcode)
+        } else {
+            None
+        };
+        Ok(self.finish_step(Some(instruction), cycles, event))
     }
 }
 
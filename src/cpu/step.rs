@@ -0,0 +1,118 @@
+use super::{Cpu, CpuError};
+use crate::mem::Bus;
+use crate::Byte;
+
+/// The interrupt sequences the CPU can run between instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupt {
+    Reset,
+    Nmi,
+    Irq,
+}
+
+/// Something other than a plain instruction happened during a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A JAM or STP opcode stopped the CPU; only a reset recovers from this.
+    Halted,
+    /// An interrupt sequence ran instead of an instruction.
+    Interrupt(Interrupt),
+    /// An opcode outside the documented instruction set was executed, see
+    /// `OpCode::is_documented`.
+    IllegalOpcode,
+}
+
+/// What a single call to `Cpu::step` did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepResult {
+    /// The opcode executed, or `None` if the step ran an interrupt sequence or the CPU
+    /// was halted or waiting.
+    pub opcode: Option<Byte>,
+    /// Cycles the step took.
+    pub cycles: u32,
+    pub event: Option<Event>,
+}
+
+/// What `Cpu::run_for` or `Cpu::run_until` did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunResult {
+    /// Cycles taken by all the steps that ran.
+    pub cycles: u64,
+    /// Cycles the last instruction ran past the requested budget. Instructions are
+    /// never cut short, so `cycles` can exceed the budget by up to seven cycles, one
+    /// less than the longest instructions take.
+    pub overshoot: u64,
+    /// Set if the run stopped early because the CPU halted.
+    pub event: Option<Event>,
+}
+
+impl Cpu {
+    /// Total cycles taken since the CPU was created, including reset sequences.
+    pub fn total_cycles(&self) -> u64 {
+        self.total_cycles
+    }
+
+    /// Runs whole instructions until at least `cycles` cycles have passed or the CPU
+    /// halts.
+    pub fn run_for<B: Bus>(&mut self, cycles: u64, memory: &mut B) -> Result<RunResult, CpuError> {
+        let mut taken: u64 = 0;
+        while taken < cycles {
+            let step: StepResult = self.step(memory)?;
+            taken += step.cycles as u64;
+            if step.event == Some(Event::Halted) {
+                return Ok(RunResult {
+                    cycles: taken,
+                    overshoot: taken.saturating_sub(cycles),
+                    event: step.event,
+                });
+            }
+        }
+        Ok(RunResult {
+            cycles: taken,
+            overshoot: taken - cycles,
+            event: None,
+        })
+    }
+
+    /// Runs whole instructions until `predicate` returns `true` or the CPU halts. The
+    /// predicate is checked before every step, so nothing runs if it already holds.
+    /// There is no budget, so `overshoot` is always zero.
+    pub fn run_until<B: Bus>(
+        &mut self,
+        memory: &mut B,
+        mut predicate: impl FnMut(&Cpu) -> bool,
+    ) -> Result<RunResult, CpuError> {
+        let mut taken: u64 = 0;
+        while !predicate(self) {
+            let step: StepResult = self.step(memory)?;
+            taken += step.cycles as u64;
+            if step.event == Some(Event::Halted) {
+                return Ok(RunResult {
+                    cycles: taken,
+                    overshoot: 0,
+                    event: step.event,
+                });
+            }
+        }
+        Ok(RunResult {
+            cycles: taken,
+            overshoot: 0,
+            event: None,
+        })
+    }
+
+    /// Adds a finished step to the cycle counter.
+    pub(super) fn finish_step(
+        &mut self,
+        opcode: Option<Byte>,
+        cycles: u32,
+        event: Option<Event>,
+    ) -> StepResult {
+        self.total_cycles += cycles as u64;
+        StepResult {
+            opcode,
+            cycles,
+            event,
+        }
+    }
+}
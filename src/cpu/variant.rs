@@ -0,0 +1,76 @@
+/// The member of the 6502 family being emulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// The original NMOS 6502.
+    Nmos6502,
+    /// The CMOS 65C02 without the Rockwell bit instructions.
+    Cmos65C02,
+    /// Rockwell R65C02: the 65C02 plus BBR, BBS, RMB and SMB.
+    Rockwell65C02,
+    /// WDC W65C02S: the Rockwell instruction set plus WAI and STP.
+    Wdc65C02,
+    /// Ricoh 2A03/2A07 used in the NES: an NMOS core with decimal mode disconnected.
+    Ricoh2A03,
+    /// MOS 6510 used in the C64: an NMOS core with an I/O port at $00/$01.
+    Mos6510,
+}
+
+impl Variant {
+    /// Whether this is one of the NMOS cores, which share the undocumented opcodes
+    /// and the bugs the 65C02 fixed.
+    pub fn is_nmos(self) -> bool {
+        matches!(
+            self,
+            Variant::Nmos6502 | Variant::Ricoh2A03 | Variant::Mos6510
+        )
+    }
+
+    /// Whether this is one of the 65C02 cores, which add STZ, BRA, PHX, PHY, PLX,
+    /// PLY, TRB, TSB and `(zp)` addressing.
+    pub fn is_cmos(self) -> bool {
+        !self.is_nmos()
+    }
+
+    /// NMOS parts fetch the high byte of `JMP ($xxFF)` from `$xx00` instead of the
+    /// next page. The 65C02 fixes this at the cost of one extra cycle.
+    pub fn has_jmp_indirect_bug(self) -> bool {
+        self.is_nmos()
+    }
+
+    /// The 65C02 clears D when it takes an interrupt; the NMOS part leaves it alone.
+    pub fn clears_decimal_on_interrupt(self) -> bool {
+        self.is_cmos()
+    }
+
+    /// Only the NMOS parts decode the undocumented opcodes (LAX, SAX, DCP, JAM, ...).
+    /// On the 65C02 every unused opcode is a NOP.
+    pub fn has_illegal_opcodes(self) -> bool {
+        self.is_nmos()
+    }
+
+    /// NMOS read-modify-write instructions write the unmodified value back before the
+    /// result. The 65C02 reads it a second time instead.
+    pub fn has_rmw_dummy_write(self) -> bool {
+        self.is_nmos()
+    }
+
+    /// The 2A03 still has the D flag, but ADC, SBC and ARR ignore it.
+    pub fn has_decimal_mode(self) -> bool {
+        self != Variant::Ricoh2A03
+    }
+
+    /// BBR, BBS, RMB and SMB.
+    pub fn has_bit_instructions(self) -> bool {
+        matches!(self, Variant::Rockwell65C02 | Variant::Wdc65C02)
+    }
+
+    /// WAI and STP.
+    pub fn has_wait_and_stop(self) -> bool {
+        self == Variant::Wdc65C02
+    }
+
+    /// The data direction register at $00 and the data register at $01.
+    pub fn has_io_port(self) -> bool {
+        self == Variant::Mos6510
+    }
+}
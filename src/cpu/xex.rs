@@ -0,0 +1,180 @@
+use super::{Cpu, CpuError, Event};
+use crate::mem::Bus;
+use crate::{Byte, Word};
+use std::error::Error;
+use std::fmt;
+
+/// RUNAD, the address DOS jumps to once the whole file is loaded.
+const RUNAD: Word = 0x02E0;
+/// INITAD, the address DOS calls as soon as the segment that sets it is loaded.
+const INITAD: Word = 0x02E2;
+
+/// What `Cpu::load_xex` loaded and ran.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XexInfo {
+    /// The first and last address of every segment, in file order.
+    pub segments: Vec<(Word, Word)>,
+    /// The INITAD routines that were called, in order.
+    pub inits: Vec<Word>,
+    /// The last RUNAD the file set, which PC now points at.
+    pub run: Option<Word>,
+}
+
+/// Why an Atari executable could not be loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XexError {
+    /// The file does not start with $FFFF.
+    MissingHeader,
+    /// The file ends inside the segment header or data at `offset`.
+    Truncated { offset: usize },
+    /// A segment whose end address is below its start address.
+    BadSegment { start: Word, end: Word },
+    /// The INITAD routine at `address` jammed the CPU.
+    InitHalted { address: Word },
+    /// The INITAD routine at `address` did not return within the cycle limit.
+    InitTimeout { address: Word },
+    /// The CPU stopped on an illegal opcode while running an INITAD routine.
+    Cpu(CpuError),
+}
+
+impl fmt::Display for XexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XexError::MissingHeader => write!(f, "file does not start with $FFFF"),
+            XexError::Truncated { offset } => write!(f, "file ends in the segment at {}", offset),
+            XexError::BadSegment { start, end } => {
+                write!(
+                    f,
+                    "segment ${:04X}-${:04X} ends before it starts",
+                    start, end
+                )
+            }
+            XexError::InitHalted { address } => {
+                write!(f, "init routine at ${:04X} halted the CPU", address)
+            }
+            XexError::InitTimeout { address } => {
+                write!(f, "init routine at ${:04X} did not return", address)
+            }
+            XexError::Cpu(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for XexError {}
+
+impl From<CpuError> for XexError {
+    fn from(error: CpuError) -> Self {
+        XexError::Cpu(error)
+    }
+}
+
+impl Cpu {
+    /// Loads an Atari 8-bit executable the way DOS does. Each segment is written to
+    /// `memory`; a segment that writes INITAD has its routine called right away, by
+    /// running the CPU until the routine returns with RTS or `init_cycles` cycles have
+    /// passed. Once all segments are loaded PC is set to RUNAD, if the file set it.
+    ///
+    /// Every segment may start with $FFFF, but only the first one has to.
+    ///
+    /// ```
+    /// use emulate_6502::cpu::{Cpu, Variant};
+    /// use emulate_6502::mem::Mem;
+    ///
+    /// let xex = [
+    ///     0xFF, 0xFF, 0x00, 0x06, 0x04, 0x06, // $0600-$0604
+    ///     0xA9, 0x2A, 0x85, 0x80, 0x60, //       LDA #$2A, STA $80, RTS
+    ///     0xE2, 0x02, 0xE3, 0x02, 0x00, 0x06, // INITAD = $0600
+    ///     0xE0, 0x02, 0xE1, 0x02, 0x00, 0x20, // RUNAD = $2000
+    /// ];
+    /// let mut memory = Mem::new();
+    /// let mut cpu = Cpu::new(Variant::Nmos6502);
+    /// cpu.reset(&mut memory);
+    /// let info = cpu.load_xex(&xex, &mut memory, 100_000).unwrap();
+    /// assert_eq!(memory[0x80], 0x2A);
+    /// assert_eq!(info.inits, vec![0x0600]);
+    /// assert_eq!(cpu.pc(), 0x2000);
+    /// ```
+    pub fn load_xex<B: Bus>(
+        &mut self,
+        data: &[Byte],
+        memory: &mut B,
+        init_cycles: u64,
+    ) -> Result<XexInfo, XexError> {
+        let mut offset: usize = 0;
+        if read_word(data, &mut offset)? != 0xFFFF {
+            return Err(XexError::MissingHeader);
+        }
+        let mut info = XexInfo::default();
+        while offset < data.len() {
+            let mut start: Word = read_word(data, &mut offset)?;
+            if start == 0xFFFF {
+                start = read_word(data, &mut offset)?;
+            }
+            let end: Word = read_word(data, &mut offset)?;
+            if end < start {
+                return Err(XexError::BadSegment { start, end });
+            }
+            let len: usize = (end - start) as usize + 1;
+            let bytes: &[Byte] = data
+                .get(offset..offset + len)
+                .ok_or(XexError::Truncated { offset })?;
+            offset += len;
+            for (index, byte) in bytes.iter().enumerate() {
+                memory.write(start + index as Word, *byte);
+            }
+            info.segments.push((start, end));
+
+            let writes = |vector: Word| start <= vector + 1 && vector <= end;
+            if writes(RUNAD) {
+                info.run = Some(peek_word(memory, RUNAD));
+            }
+            if writes(INITAD) {
+                let address: Word = peek_word(memory, INITAD);
+                self.call_init(address, memory, init_cycles)?;
+                info.inits.push(address);
+            }
+        }
+        if let Some(run) = info.run {
+            self.PC = run;
+        }
+        Ok(info)
+    }
+
+    /// Runs the subroutine at `address` as if it had been called with JSR from PC,
+    /// returning once its RTS brings SP back to where it was.
+    fn call_init<B: Bus>(
+        &mut self,
+        address: Word,
+        memory: &mut B,
+        cycle_limit: u64,
+    ) -> Result<(), XexError> {
+        let return_sp: Byte = self.SP;
+        self.push_word(self.PC.wrapping_sub(1), memory);
+        self.PC = address;
+        let mut taken: u64 = 0;
+        while self.SP != return_sp {
+            if taken >= cycle_limit {
+                return Err(XexError::InitTimeout { address });
+            }
+            let step = self.step(memory)?;
+            if step.event == Some(Event::Halted) {
+                return Err(XexError::InitHalted { address });
+            }
+            taken += step.cycles as u64;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a little-endian word from the file and moves `offset` past it.
+fn read_word(data: &[Byte], offset: &mut usize) -> Result<Word, XexError> {
+    let bytes: &[Byte] = data
+        .get(*offset..*offset + 2)
+        .ok_or(XexError::Truncated { offset: *offset })?;
+    *offset += 2;
+    Ok(Word::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn peek_word<B: Bus>(memory: &B, address: Word) -> Word {
+    Word::from_le_bytes([memory.peek(address), memory.peek(address + 1)])
+}
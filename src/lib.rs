@@ -0,0 +1,6 @@
+pub mod cpu;
+pub mod mem;
+pub mod opcodes;
+
+pub type Byte = u8;
+pub type Word = u16;
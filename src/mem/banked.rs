@@ -0,0 +1,279 @@
+use super::{Bus, Mem};
+use crate::{Byte, Word};
+use std::error::Error;
+use std::fmt;
+
+/// Identifies a bank added with `BankedMem::add_bank`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BankId(usize);
+
+/// Identifies a window added with `BankedMem::add_window`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WindowId(usize);
+
+/// Mistakes in how a `BankedMem` is set up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BankError {
+    /// A bank with no data.
+    EmptyBank(String),
+    /// A window whose start is above its end.
+    EmptyWindow { start: Word, end: Word },
+    /// A window that shares addresses with one added before it.
+    Overlap {
+        first: (Word, Word),
+        second: (Word, Word),
+    },
+    /// A `WindowId` this `BankedMem` never handed out.
+    UnknownWindow(WindowId),
+    /// A `BankId` this `BankedMem` never handed out.
+    UnknownBank(BankId),
+}
+
+impl fmt::Display for BankError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BankError::EmptyBank(name) => write!(f, "bank {} is empty", name),
+            BankError::EmptyWindow { start, end } => {
+                write!(f, "window ${:04X}-${:04X} is empty", start, end)
+            }
+            BankError::Overlap { first, second } => write!(
+                f,
+                "window ${:04X}-${:04X} overlaps ${:04X}-${:04X}",
+                second.0, second.1, first.0, first.1
+            ),
+            BankError::UnknownWindow(window) => write!(f, "unknown window {}", window.0),
+            BankError::UnknownBank(bank) => write!(f, "unknown bank {}", bank.0),
+        }
+    }
+}
+
+impl Error for BankError {}
+
+struct Bank {
+    name: String,
+    data: Vec<Byte>,
+    writable: bool,
+}
+
+struct Window {
+    start: Word,
+    end: Word,
+    /// `None` lets the underlying `Mem` show through.
+    bank: Option<BankId>,
+}
+
+/// Latches that switch a window when the CPU writes to `address`.
+struct Register {
+    address: Word,
+    window: WindowId,
+    select: Box<dyn Fn(Byte) -> Option<BankId>>,
+}
+
+/// Flat RAM with switchable windows on top. Each window shows one of the named banks,
+/// or the RAM beneath it when no bank is mapped; writes to configured register
+/// addresses switch what a window shows.
+///
+/// ```
+/// use emulate_6502::mem::{format_address, BankedMem, Bus, Mem};
+///
+/// let mut memory = BankedMem::new(Mem::new());
+/// let rom0 = memory.add_bank("ROM0", vec![0x00; 0x4000], false).unwrap();
+/// let rom1 = memory.add_bank("ROM1", vec![0x11; 0x4000], false).unwrap();
+/// let window = memory.add_window(0x8000, 0xBFFF).unwrap();
+/// memory.map(window, Some(rom0)).unwrap();
+/// // Writing 1 to $FFF0 selects ROM1.
+/// memory
+///     .switch_on_write(0xFFF0, window, move |value| Some([rom0, rom1][value as usize & 1]))
+///     .unwrap();
+/// memory.write(0xFFF0, 1);
+/// assert_eq!(memory.read(0x8000), 0x11);
+/// assert_eq!(format_address(&memory, 0x8000), "ROM1:$8000");
+/// ```
+pub struct BankedMem {
+    memory: Mem,
+    banks: Vec<Bank>,
+    windows: Vec<Window>,
+    registers: Vec<Register>,
+}
+
+impl BankedMem {
+    pub fn new(memory: Mem) -> Self {
+        BankedMem {
+            memory,
+            banks: Vec::new(),
+            windows: Vec::new(),
+            registers: Vec::new(),
+        }
+    }
+
+    /// The RAM beneath the windows.
+    pub fn memory(&self) -> &Mem {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut Mem {
+        &mut self.memory
+    }
+
+    /// Adds a bank holding `data`, which must not be empty. Banks shorter than a
+    /// window they are mapped into repeat; ROM banks ignore writes.
+    pub fn add_bank(
+        &mut self,
+        name: &str,
+        data: Vec<Byte>,
+        writable: bool,
+    ) -> Result<BankId, BankError> {
+        if data.is_empty() {
+            return Err(BankError::EmptyBank(name.to_string()));
+        }
+        self.banks.push(Bank {
+            name: name.to_string(),
+            data,
+            writable,
+        });
+        Ok(BankId(self.banks.len() - 1))
+    }
+
+    /// Adds a window covering `start..=end`, initially showing the RAM beneath it.
+    /// Windows must not overlap.
+    pub fn add_window(&mut self, start: Word, end: Word) -> Result<WindowId, BankError> {
+        if start > end {
+            return Err(BankError::EmptyWindow { start, end });
+        }
+        if let Some(window) = self
+            .windows
+            .iter()
+            .find(|window| start <= window.end && window.start <= end)
+        {
+            return Err(BankError::Overlap {
+                first: (window.start, window.end),
+                second: (start, end),
+            });
+        }
+        self.windows.push(Window {
+            start,
+            end,
+            bank: None,
+        });
+        Ok(WindowId(self.windows.len() - 1))
+    }
+
+    /// Maps `bank` into `window`, or unmaps it with `None`.
+    pub fn map(&mut self, window: WindowId, bank: Option<BankId>) -> Result<(), BankError> {
+        self.check_window(window)?;
+        if let Some(bank) = bank {
+            self.check_bank(bank)?;
+        }
+        self.windows[window.0].bank = bank;
+        Ok(())
+    }
+
+    /// Makes writes to `address` switch `window` to the bank `select` picks for the
+    /// written value. The write goes to the register only, not to memory. Several
+    /// registers may share an address, e.g. to switch more than one window at once.
+    /// If `select` picks a bank this `BankedMem` does not have, the window is left
+    /// as it was.
+    pub fn switch_on_write(
+        &mut self,
+        address: Word,
+        window: WindowId,
+        select: impl Fn(Byte) -> Option<BankId> + 'static,
+    ) -> Result<(), BankError> {
+        self.check_window(window)?;
+        self.registers.push(Register {
+            address,
+            window,
+            select: Box::new(select),
+        });
+        Ok(())
+    }
+
+    /// The bank currently mapped into `window`.
+    pub fn mapped_bank(&self, window: WindowId) -> Result<Option<BankId>, BankError> {
+        self.check_window(window)?;
+        Ok(self.windows[window.0].bank)
+    }
+
+    /// The bank `address` currently reads from and the offset into it, or `None` if
+    /// it reads the RAM beneath.
+    pub fn bank_at(&self, address: Word) -> Option<(BankId, usize)> {
+        let window: &Window = self
+            .windows
+            .iter()
+            .find(|window| (window.start..=window.end).contains(&address))?;
+        let bank: BankId = window.bank?;
+        let offset: usize = (address - window.start) as usize % self.banks[bank.0].data.len();
+        Some((bank, offset))
+    }
+
+    /// The windows and the bank each one shows, in the order they were added.
+    pub fn mapping(&self) -> Vec<(Word, Word, Option<BankId>)> {
+        self.windows
+            .iter()
+            .map(|window| (window.start, window.end, window.bank))
+            .collect()
+    }
+
+    pub fn name(&self, bank: BankId) -> Result<&str, BankError> {
+        self.check_bank(bank)?;
+        Ok(&self.banks[bank.0].name)
+    }
+
+    /// Ids are plain indices, so only ones past the end are caught; an id from
+    /// another `BankedMem` that happens to be in range refers to a window here.
+    fn check_window(&self, window: WindowId) -> Result<(), BankError> {
+        if window.0 >= self.windows.len() {
+            return Err(BankError::UnknownWindow(window));
+        }
+        Ok(())
+    }
+
+    /// See `check_window`.
+    fn check_bank(&self, bank: BankId) -> Result<(), BankError> {
+        if bank.0 >= self.banks.len() {
+            return Err(BankError::UnknownBank(bank));
+        }
+        Ok(())
+    }
+}
+
+impl Bus for BankedMem {
+    fn read(&mut self, address: Word) -> Byte {
+        self.peek(address)
+    }
+
+    fn write(&mut self, address: Word, value: Byte) {
+        let mut switched: bool = false;
+        for register in self.registers.iter().filter(|r| r.address == address) {
+            let bank: Option<BankId> = (register.select)(value);
+            if bank.is_none_or(|bank| bank.0 < self.banks.len()) {
+                self.windows[register.window.0].bank = bank;
+            }
+            switched = true;
+        }
+        if switched {
+            return;
+        }
+        match self.bank_at(address) {
+            Some((bank, offset)) => {
+                let bank: &mut Bank = &mut self.banks[bank.0];
+                if bank.writable {
+                    bank.data[offset] = value;
+                }
+            }
+            None => self.memory[address] = value,
+        }
+    }
+
+    fn peek(&self, address: Word) -> Byte {
+        match self.bank_at(address) {
+            Some((bank, offset)) => self.banks[bank.0].data[offset],
+            None => self.memory[address],
+        }
+    }
+
+    fn bank_name(&self, address: Word) -> Option<&str> {
+        let (bank, _) = self.bank_at(address)?;
+        self.name(bank).ok()
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{Byte, Word};
+
+/// Whatever sits on the CPU's address and data lines: RAM, ROM, I/O devices or a whole
+/// machine's memory map.
+pub trait Bus {
+    /// Reads the byte at `address`. Devices may react to the access, e.g. by
+    /// acknowledging an interrupt or advancing a FIFO.
+    fn read(&mut self, address: Word) -> Byte;
+
+    /// Writes `value` to `address`.
+    fn write(&mut self, address: Word, value: Byte);
+
+    /// Returns what a read of `address` would, without any side effects. Meant for
+    /// debuggers and disassemblers.
+    fn peek(&self, address: Word) -> Byte;
+
+    /// The name of the bank `address` is currently mapped to, on buses that switch
+    /// banks.
+    fn bank_name(&self, _address: Word) -> Option<&str> {
+        None
+    }
+}
+
+/// Formats `address` the way debuggers and disassemblers show it: `BANK:$ADDR` when
+/// the bus maps it to a named bank, `$ADDR` otherwise.
+pub fn format_address<B: Bus + ?Sized>(bus: &B, address: Word) -> String {
+    match bus.bank_name(address) {
+        Some(bank) => format!("{}:${:04X}", bank, address),
+        None => format!("${:04X}", address),
+    }
+}
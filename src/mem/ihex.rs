@@ -0,0 +1,292 @@
+use super::Mem;
+use crate::{Byte, Word};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// A line of an Intel HEX file that could not be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexError {
+    /// 1-based line number.
+    pub line: usize,
+    pub kind: HexErrorKind,
+}
+
+/// What was wrong with a line of an Intel HEX file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HexErrorKind {
+    /// The line does not start with `:`.
+    MissingColon,
+    /// The line holds something other than pairs of hex digits.
+    BadDigits,
+    /// The byte count does not match the number of data bytes.
+    BadLength { declared: usize, found: usize },
+    /// The checksum does not match the rest of the record.
+    BadChecksum { expected: Byte, found: Byte },
+    /// A record type other than 00, 01, 04 or 05.
+    UnsupportedRecord(Byte),
+    /// An end-of-file or address record with the wrong number of data bytes.
+    BadRecordLength { record: Byte, len: usize },
+    /// Data or a start address above $FFFF. For data this is the first address past
+    /// the end of memory that the record would write.
+    OutOfRange(u32),
+    /// The file ended without an end-of-file record.
+    MissingEndOfFile,
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            HexErrorKind::MissingColon => write!(f, "record does not start with ':'"),
+            HexErrorKind::BadDigits => write!(f, "record is not made of hex byte pairs"),
+            HexErrorKind::BadLength { declared, found } => write!(
+                f,
+                "byte count is {} but the record holds {} data bytes",
+                declared, found
+            ),
+            HexErrorKind::BadChecksum { expected, found } => {
+                write!(f, "checksum is ${:02X}, expected ${:02X}", found, expected)
+            }
+            HexErrorKind::UnsupportedRecord(record) => {
+                write!(f, "unsupported record type {:02X}", record)
+            }
+            HexErrorKind::BadRecordLength { record, len } => {
+                write!(f, "record type {:02X} cannot hold {} bytes", record, len)
+            }
+            HexErrorKind::OutOfRange(address) => {
+                write!(f, "address ${:X} is above $FFFF", address)
+            }
+            HexErrorKind::MissingEndOfFile => write!(f, "missing end-of-file record"),
+        }
+    }
+}
+
+impl Error for HexError {}
+
+/// Data bytes per record written by `Mem::to_ihex`.
+const BYTES_PER_RECORD: usize = 16;
+
+const RECORD_DATA: Byte = 0x00;
+const RECORD_END_OF_FILE: Byte = 0x01;
+const RECORD_EXTENDED_LINEAR_ADDRESS: Byte = 0x04;
+const RECORD_START_LINEAR_ADDRESS: Byte = 0x05;
+
+impl Mem {
+    /// Loads an Intel HEX image and returns the address from its start record, if it
+    /// has one, for seeding PC with `Cpu::set_pc`. Data records and extended linear
+    /// address records (04) must keep everything below $10000. Nothing is written
+    /// unless the whole file is valid.
+    ///
+    /// ```
+    /// use emulate_6502::cpu::{Cpu, Variant};
+    /// use emulate_6502::mem::Mem;
+    ///
+    /// let mut memory = Mem::new();
+    /// let start = memory
+    ///     .load_ihex(":03020000A9428A86\n:0400000500000200F5\n:00000001FF\n")
+    ///     .unwrap();
+    /// assert_eq!(memory[0x0201], 0x42);
+    ///
+    /// let mut cpu = Cpu::new(Variant::Nmos6502);
+    /// cpu.reset(&mut memory);
+    /// cpu.set_pc(start.unwrap());
+    /// assert_eq!(cpu.pc(), 0x0200);
+    /// ```
+    ///
+    /// Data that would run past $FFFF is an error, even when an 04 record pushes it
+    /// to the very top of the 32-bit range:
+    ///
+    /// ```
+    /// use emulate_6502::mem::{HexErrorKind, Mem};
+    ///
+    /// let error = Mem::new()
+    ///     .load_ihex(":02000004FFFFFC\n:02FFFF00EAEA2C\n:00000001FF\n")
+    ///     .unwrap_err();
+    /// assert_eq!(error.line, 2);
+    /// assert_eq!(error.kind, HexErrorKind::OutOfRange(0xFFFF_FFFF));
+    /// ```
+    pub fn load_ihex(&mut self, text: &str) -> Result<Option<Word>, HexError> {
+        let mut chunks: Vec<(Word, Vec<Byte>)> = Vec::new();
+        let mut start: Option<Word> = None;
+        let mut upper: u32 = 0;
+        let mut line_count: usize = 0;
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number: usize = index + 1;
+            line_count = line_number;
+            let line: &str = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |kind: HexErrorKind| HexError {
+                line: line_number,
+                kind,
+            };
+            let (record, address, data) = parse_record(line).map_err(error)?;
+            match record {
+                RECORD_DATA => {
+                    let address: u32 = upper | address as u32;
+                    if address as u64 + data.len() as u64 > 0x10000 {
+                        // The first byte that lands above $FFFF.
+                        return Err(error(HexErrorKind::OutOfRange(address.max(0x10000))));
+                    }
+                    chunks.push((address as Word, data));
+                }
+                RECORD_END_OF_FILE => {
+                    if !data.is_empty() {
+                        return Err(error(HexErrorKind::BadRecordLength {
+                            record,
+                            len: data.len(),
+                        }));
+                    }
+                    for (address, data) in chunks {
+                        let start: usize = address as usize;
+                        self.data[start..start + data.len()].copy_from_slice(&data);
+                    }
+                    return Ok(start);
+                }
+                RECORD_EXTENDED_LINEAR_ADDRESS => {
+                    if data.len() != 2 {
+                        return Err(error(HexErrorKind::BadRecordLength {
+                            record,
+                            len: data.len(),
+                        }));
+                    }
+                    upper = u32::from_be_bytes([data[0], data[1], 0, 0]);
+                }
+                RECORD_START_LINEAR_ADDRESS => {
+                    let bytes: [Byte; 4] = data.as_slice().try_into().map_err(|_| {
+                        error(HexErrorKind::BadRecordLength {
+                            record,
+                            len: data.len(),
+                        })
+                    })?;
+                    let address: u32 = u32::from_be_bytes(bytes);
+                    if address > 0xFFFF {
+                        return Err(error(HexErrorKind::OutOfRange(address)));
+                    }
+                    start = Some(address as Word);
+                }
+                _ => return Err(error(HexErrorKind::UnsupportedRecord(record))),
+            }
+        }
+
+        Err(HexError {
+            line: line_count + 1,
+            kind: HexErrorKind::MissingEndOfFile,
+        })
+    }
+
+    /// Loads an Intel HEX file from `path`, see `load_ihex`. Malformed lines are
+    /// reported as `InvalidData` errors wrapping a `HexError`.
+    pub fn load_ihex_file(&mut self, path: impl AsRef<Path>) -> io::Result<Option<Word>> {
+        let text: String = fs::read_to_string(path)?;
+        self.load_ihex(&text)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Formats `ranges` as Intel HEX, 16 data bytes per record, followed by a start
+    /// record if `start` is given and the end-of-file record. Fails on an empty range.
+    pub fn to_ihex(
+        &self,
+        ranges: &[RangeInclusive<Word>],
+        start: Option<Word>,
+    ) -> io::Result<String> {
+        let mut text = String::new();
+        for range in ranges {
+            let bytes: &[Byte] = self.range_bytes(*range.start(), *range.end())?;
+            for (index, chunk) in bytes.chunks(BYTES_PER_RECORD).enumerate() {
+                let address: Word = range.start() + (index * BYTES_PER_RECORD) as Word;
+                push_record(&mut text, RECORD_DATA, address, chunk);
+            }
+        }
+        if let Some(start) = start {
+            push_record(
+                &mut text,
+                RECORD_START_LINEAR_ADDRESS,
+                0,
+                &(start as u32).to_be_bytes(),
+            );
+        }
+        push_record(&mut text, RECORD_END_OF_FILE, 0, &[]);
+        Ok(text)
+    }
+
+    /// Writes `ranges` to `path` as Intel HEX, see `to_ihex`.
+    pub fn write_to_ihex(
+        &self,
+        path: impl AsRef<Path>,
+        ranges: &[RangeInclusive<Word>],
+        start: Option<Word>,
+    ) -> io::Result<()> {
+        fs::write(path, self.to_ihex(ranges, start)?)
+    }
+}
+
+/// Splits `:LLAAAATT<data>CC` into its record type, address and data after checking
+/// the byte count and checksum.
+fn parse_record(line: &str) -> Result<(Byte, Word, Vec<Byte>), HexErrorKind> {
+    let digits: &str = line.strip_prefix(':').ok_or(HexErrorKind::MissingColon)?;
+    let bytes: Vec<Byte> = decode_hex(digits).ok_or(HexErrorKind::BadDigits)?;
+    if bytes.len() < 5 {
+        return Err(HexErrorKind::BadDigits);
+    }
+    let declared: usize = bytes[0] as usize;
+    let found: usize = bytes.len() - 5;
+    if declared != found {
+        return Err(HexErrorKind::BadLength { declared, found });
+    }
+    let (body, checksum) = bytes.split_at(bytes.len() - 1);
+    let expected: Byte = checksum_of(body);
+    if checksum[0] != expected {
+        return Err(HexErrorKind::BadChecksum {
+            expected,
+            found: checksum[0],
+        });
+    }
+    let address: Word = Word::from_be_bytes([body[1], body[2]]);
+    Ok((body[3], address, body[4..].to_vec()))
+}
+
+/// Two's complement of the sum of `bytes`, so that a record including its checksum
+/// sums to zero.
+fn checksum_of(bytes: &[Byte]) -> Byte {
+    bytes
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg()
+}
+
+fn push_record(text: &mut String, record: Byte, address: Word, data: &[Byte]) {
+    let mut bytes: Vec<Byte> = vec![data.len() as Byte];
+    bytes.extend_from_slice(&address.to_be_bytes());
+    bytes.push(record);
+    bytes.extend_from_slice(data);
+    bytes.push(checksum_of(&bytes));
+    text.push(':');
+    push_hex(text, &bytes);
+    text.push('\n');
+}
+
+/// Decodes pairs of hex digits, or returns `None` if `digits` is anything else.
+/// Shared with the S-record reader.
+pub(super) fn decode_hex(digits: &str) -> Option<Vec<Byte>> {
+    if !digits.len().is_multiple_of(2) || !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| Byte::from_str_radix(&digits[index..index + 2], 16).ok())
+        .collect()
+}
+
+/// Appends `bytes` to `text` as upper case hex digit pairs.
+pub(super) fn push_hex(text: &mut String, bytes: &[Byte]) {
+    for byte in bytes {
+        text.push_str(&format!("{:02X}", byte));
+    }
+}
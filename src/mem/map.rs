@@ -0,0 +1,286 @@
+use super::Bus;
+use crate::{Byte, Word};
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// What backs one region of a `MemoryMap`.
+enum Kind {
+    Ram(Vec<Byte>),
+    /// Writes are ignored.
+    Rom(Vec<Byte>),
+    /// Sees addresses relative to the start of its region.
+    Device(Box<dyn Bus>),
+    /// Redirects to `source`, repeating it as often as fits.
+    Mirror(RangeInclusive<Word>),
+}
+
+struct Region {
+    range: RangeInclusive<Word>,
+    kind: Kind,
+}
+
+/// Problems found while building a `MemoryMap`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapError {
+    /// Two regions claim the same address.
+    Overlap {
+        first: RangeInclusive<Word>,
+        second: RangeInclusive<Word>,
+    },
+    /// A ROM image is empty or runs past $FFFF.
+    BadRom { start: Word, len: usize },
+    /// A range whose start is above its end.
+    EmptyRange(RangeInclusive<Word>),
+    /// A mirror whose source is not entirely mapped to RAM, ROM or a device.
+    BadMirror {
+        range: RangeInclusive<Word>,
+        source: RangeInclusive<Word>,
+    },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::Overlap { first, second } => write!(
+                f,
+                "${:04X}-${:04X} overlaps ${:04X}-${:04X}",
+                second.start(),
+                second.end(),
+                first.start(),
+                first.end()
+            ),
+            MapError::BadRom { start, len } => {
+                write!(f, "ROM of {} bytes does not fit at ${:04X}", len, start)
+            }
+            MapError::EmptyRange(range) => {
+                write!(f, "empty range ${:04X}-${:04X}", range.start(), range.end())
+            }
+            MapError::BadMirror { range, source } => write!(
+                f,
+                "mirror ${:04X}-${:04X} of ${:04X}-${:04X} does not point at mapped memory",
+                range.start(),
+                range.end(),
+                source.start(),
+                source.end()
+            ),
+        }
+    }
+}
+
+impl Error for MapError {}
+
+/// Composes RAM, ROM, mirrors and device handlers into one `Bus`. Addresses no region
+/// claims are open bus: reads return whatever was last on the data bus.
+///
+/// ```
+/// use emulate_6502::mem::MemoryMap;
+///
+/// let map = MemoryMap::builder()
+///     .ram(0x0000..=0x07FF)
+///     .mirror(0x0800..=0x1FFF, 0x0000..=0x07FF)
+///     .rom(0xC000, vec![0xEA; 0x4000])
+///     .build()
+///     .unwrap();
+/// ```
+pub struct MemoryMap {
+    /// Sorted by start address, without overlaps.
+    regions: Vec<Region>,
+    /// The last value read or written.
+    open_bus: Byte,
+}
+
+/// Collects regions for a `MemoryMap`; nothing is checked until `build`.
+#[derive(Default)]
+pub struct MemoryMapBuilder {
+    regions: Vec<Region>,
+    errors: Vec<MapError>,
+}
+
+impl MemoryMapBuilder {
+    /// Zero-filled RAM covering `range`.
+    pub fn ram(mut self, range: RangeInclusive<Word>) -> Self {
+        let len: usize = range_len(&range);
+        self.push(range, Kind::Ram(vec![0; len]));
+        self
+    }
+
+    /// Write-protected memory holding `data`, starting at `start`.
+    pub fn rom(mut self, start: Word, data: Vec<Byte>) -> Self {
+        let end: usize = start as usize + data.len();
+        if data.is_empty() || end > 0x10000 {
+            self.errors.push(MapError::BadRom {
+                start,
+                len: data.len(),
+            });
+            return self;
+        }
+        self.push(start..=(end - 1) as Word, Kind::Rom(data));
+        self
+    }
+
+    /// Hands every access in `range` to `device`, as an offset from the start of the
+    /// range.
+    pub fn device(mut self, range: RangeInclusive<Word>, device: Box<dyn Bus>) -> Self {
+        self.push(range, Kind::Device(device));
+        self
+    }
+
+    /// Makes `range` show `source`, repeated as often as fits. For example the NES
+    /// mirrors its 2 KiB of RAM with `mirror(0x0800..=0x1FFF, 0x0000..=0x07FF)`.
+    pub fn mirror(mut self, range: RangeInclusive<Word>, source: RangeInclusive<Word>) -> Self {
+        if source.is_empty() {
+            self.errors.push(MapError::EmptyRange(source));
+            return self;
+        }
+        self.push(range, Kind::Mirror(source));
+        self
+    }
+
+    /// Checks the regions and builds the map. Fails on the first overlap, empty range,
+    /// bad ROM or mirror of unmapped memory.
+    pub fn build(mut self) -> Result<MemoryMap, MapError> {
+        if let Some(error) = self.errors.into_iter().next() {
+            return Err(error);
+        }
+        self.regions.sort_by_key(|region| *region.range.start());
+        for pair in self.regions.windows(2) {
+            if pair[1].range.start() <= pair[0].range.end() {
+                return Err(MapError::Overlap {
+                    first: pair[0].range.clone(),
+                    second: pair[1].range.clone(),
+                });
+            }
+        }
+        let map = MemoryMap {
+            regions: self.regions,
+            open_bus: 0,
+        };
+        for region in &map.regions {
+            if let Kind::Mirror(source) = &region.kind {
+                let mapped = source.clone().all(|address| {
+                    matches!(
+                        map.find(address).map(|region| &region.kind),
+                        Some(Kind::Ram(_) | Kind::Rom(_) | Kind::Device(_))
+                    )
+                });
+                if !mapped {
+                    return Err(MapError::BadMirror {
+                        range: region.range.clone(),
+                        source: source.clone(),
+                    });
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    fn push(&mut self, range: RangeInclusive<Word>, kind: Kind) {
+        if range.is_empty() {
+            self.errors.push(MapError::EmptyRange(range));
+        } else {
+            self.regions.push(Region { range, kind });
+        }
+    }
+}
+
+impl MemoryMap {
+    pub fn builder() -> MemoryMapBuilder {
+        MemoryMapBuilder::default()
+    }
+
+    fn find(&self, address: Word) -> Option<&Region> {
+        let index: usize = self
+            .regions
+            .partition_point(|region| *region.range.end() < address);
+        self.regions
+            .get(index)
+            .filter(|region| region.range.contains(&address))
+    }
+
+    fn find_mut(&mut self, address: Word) -> Option<&mut Region> {
+        let index: usize = self
+            .regions
+            .partition_point(|region| *region.range.end() < address);
+        self.regions
+            .get_mut(index)
+            .filter(|region| region.range.contains(&address))
+    }
+
+    /// Follows a mirror to the address it shows. Other addresses are returned as is.
+    fn resolve(&self, address: Word) -> Word {
+        match self
+            .find(address)
+            .map(|region| (&region.range, &region.kind))
+        {
+            Some((range, Kind::Mirror(source))) => {
+                let offset: usize = (address - range.start()) as usize % range_len(source);
+                source.start() + offset as Word
+            }
+            _ => address,
+        }
+    }
+}
+
+impl Bus for MemoryMap {
+    fn read(&mut self, address: Word) -> Byte {
+        let address: Word = self.resolve(address);
+        let open_bus: Byte = self.open_bus;
+        let value: Byte = match self.find_mut(address) {
+            Some(region) => {
+                let offset: Word = address - region.range.start();
+                match &mut region.kind {
+                    Kind::Ram(data) | Kind::Rom(data) => data[offset as usize],
+                    Kind::Device(device) => device.read(offset),
+                    Kind::Mirror(_) => open_bus,
+                }
+            }
+            None => open_bus,
+        };
+        self.open_bus = value;
+        value
+    }
+
+    fn write(&mut self, address: Word, value: Byte) {
+        self.open_bus = value;
+        let address: Word = self.resolve(address);
+        if let Some(region) = self.find_mut(address) {
+            let offset: Word = address - region.range.start();
+            match &mut region.kind {
+                Kind::Ram(data) => data[offset as usize] = value,
+                Kind::Device(device) => device.write(offset, value),
+                Kind::Rom(_) | Kind::Mirror(_) => {}
+            }
+        }
+    }
+
+    fn peek(&self, address: Word) -> Byte {
+        let address: Word = self.resolve(address);
+        match self.find(address) {
+            Some(region) => {
+                let offset: Word = address - region.range.start();
+                match &region.kind {
+                    Kind::Ram(data) | Kind::Rom(data) => data[offset as usize],
+                    Kind::Device(device) => device.peek(offset),
+                    Kind::Mirror(_) => self.open_bus,
+                }
+            }
+            None => self.open_bus,
+        }
+    }
+
+    /// Devices that switch banks report them, with the address as an offset into the
+    /// device.
+    fn bank_name(&self, address: Word) -> Option<&str> {
+        let address: Word = self.resolve(address);
+        let region: &Region = self.find(address)?;
+        match &region.kind {
+            Kind::Device(device) => device.bank_name(address - region.range.start()),
+            _ => None,
+        }
+    }
+}
+
+fn range_len(range: &RangeInclusive<Word>) -> usize {
+    (*range.end() as usize + 1).saturating_sub(*range.start() as usize)
+}
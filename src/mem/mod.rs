@@ -1,18 +1,45 @@
+use std::fs;
+use std::io;
 use std::ops::{Index, IndexMut};
+use std::path::Path;
 
 #[warn(unused_imports)]
 use crate::{Byte, Word};
+
+mod banked;
+mod bus;
+mod ihex;
+mod map;
+mod o65;
+mod prg;
+mod srec;
+
+pub use banked::{BankError, BankId, BankedMem, WindowId};
+pub use bus::{format_address, Bus};
+pub use ihex::{HexError, HexErrorKind};
+pub use map::{MapError, MemoryMap, MemoryMapBuilder};
+pub use o65::{O65Bases, O65Error, O65Segment, O65};
+pub use prg::PrgInfo;
+pub use srec::{SrecError, SrecErrorKind};
+
+/// 64 KiB of flat RAM, the simplest `Bus`.
 #[derive(Debug)]
 pub struct Mem {
     pub data: Vec<Byte>,
 }
 
+impl Default for Mem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Mem {
     const MAX_MEM: u32 = 1024 * 64;
 
     pub fn new() -> Self {
         Mem {
-            data: Vec::with_capacity(Self::MAX_MEM.try_into().unwrap()),
+            data: vec![0; Self::MAX_MEM.try_into().unwrap()],
         }
     }
 
@@ -20,20 +47,67 @@ impl Mem {
         self.data = vec![0; Self::MAX_MEM.try_into().unwrap()];
     }
 
-    pub fn wtire_word(&mut self, value: Word, address: Word, cycles: &mut u32) {
-        self.data[address as usize] = (value & 0xFF) as Byte;
-        self.data[(address + 1) as usize] = (value >> 8) as Byte;
-        *cycles -= 2;
+    /// Copies `data` into memory starting at `address`. Fails without writing anything
+    /// if the data would run past $FFFF.
+    pub fn load_slice(&mut self, address: Word, data: &[Byte]) -> io::Result<()> {
+        let start: usize = address as usize;
+        let end: usize = Self::checked_end(address, data.len())?;
+        self.data[start..end].copy_from_slice(data);
+        Ok(())
     }
 
-    pub fn write_to_bin(&self, path: &str) {
-        use std::fs::File;
-        use std::io::Write;
+    /// Loads a raw binary image from `path` at `address`.
+    pub fn load_bin(&mut self, path: impl AsRef<Path>, address: Word) -> io::Result<()> {
+        let data: Vec<Byte> = fs::read(path)?;
+        self.load_slice(address, &data)
+    }
+
+    /// Writes `start..=end` to `path` as a raw binary image.
+    pub fn dump_range(&self, path: impl AsRef<Path>, start: Word, end: Word) -> io::Result<()> {
+        fs::write(path, self.range_bytes(start, end)?)
+    }
+
+    /// Writes all 64 KiB to `path`.
+    pub fn write_to_bin(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, &self.data)
+    }
 
-        let mut file = File::create(path).unwrap();
-        for byte in &self.data {
-            file.write_all(&[*byte]).unwrap();
+    /// The bytes in `start..=end`. Fails if `start` is above `end`, so every export
+    /// rejects an empty range the same way.
+    fn range_bytes(&self, start: Word, end: Word) -> io::Result<&[Byte]> {
+        if start > end {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("range ${:04X}-${:04X} is empty", start, end),
+            ));
         }
+        Ok(&self.data[start as usize..=end as usize])
+    }
+
+    /// End of `len` bytes placed at `address`, as an exclusive index into `data`.
+    fn checked_end(address: Word, len: usize) -> io::Result<usize> {
+        let end: usize = address as usize + len;
+        if end > Self::MAX_MEM as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} bytes at ${:04X} run past $FFFF", len, address),
+            ));
+        }
+        Ok(end)
+    }
+}
+
+impl Bus for Mem {
+    fn read(&mut self, address: Word) -> Byte {
+        self[address]
+    }
+
+    fn write(&mut self, address: Word, value: Byte) {
+        self[address] = value;
+    }
+
+    fn peek(&self, address: Word) -> Byte {
+        self[address]
     }
 }
 
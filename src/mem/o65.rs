@@ -0,0 +1,434 @@
+use super::Mem;
+use crate::{Byte, Word};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// The bytes every o65 file starts with: a non-C64 marker, "o65" and version 0.
+const MAGIC: [Byte; 6] = [0x01, 0x00, b'o', b'6', b'5', 0x00];
+
+/// Mode bit for relocation tables that only relocate whole pages.
+const MODE_PAGED: Word = 0x4000;
+/// Mode bit for 32-bit sizes and addresses.
+const MODE_SIZE32: Word = 0x2000;
+/// Mode bit asking for the bss segment to be cleared.
+const MODE_BSS_ZERO: Word = 0x0200;
+
+const RELOC_WORD: Byte = 0x80;
+const RELOC_HIGH: Byte = 0x40;
+const RELOC_LOW: Byte = 0x20;
+
+const SEGMENT_UNDEFINED: Byte = 0;
+const SEGMENT_ABSOLUTE: Byte = 1;
+const SEGMENT_TEXT: Byte = 2;
+const SEGMENT_DATA: Byte = 3;
+const SEGMENT_BSS: Byte = 4;
+const SEGMENT_ZERO: Byte = 5;
+
+/// Why an o65 object could not be parsed or loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum O65Error {
+    /// The file does not start with the o65 marker.
+    BadMagic,
+    /// The file ends inside whatever starts at `offset`.
+    Truncated { offset: usize },
+    /// A mode this loader cannot handle, e.g. 32-bit sizes.
+    UnsupportedMode(Word),
+    /// A relocation entry with an unknown type or segment, or one that points outside
+    /// its segment.
+    BadRelocation { offset: usize },
+    /// A reference to an undefined symbol that is missing from the caller's map.
+    UndefinedSymbol(String),
+    /// A segment that does not fit at the base it was given. Zero page segments must
+    /// stay below $100.
+    DoesNotFit { base: Word, len: Word },
+    /// An object with page-wise relocation given a base that moves a segment by part
+    /// of a page; `assembled` is where the segment was assembled.
+    MisalignedBase { base: Word, assembled: Word },
+}
+
+impl fmt::Display for O65Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            O65Error::BadMagic => write!(f, "not an o65 file"),
+            O65Error::Truncated { offset } => write!(f, "file ends at offset {}", offset),
+            O65Error::UnsupportedMode(mode) => write!(f, "unsupported mode ${:04X}", mode),
+            O65Error::BadRelocation { offset } => {
+                write!(f, "bad relocation entry at offset {}", offset)
+            }
+            O65Error::UndefinedSymbol(name) => write!(f, "undefined symbol {}", name),
+            O65Error::DoesNotFit { base, len } => {
+                write!(f, "{} bytes do not fit at ${:04X}", len, base)
+            }
+            O65Error::MisalignedBase { base, assembled } => write!(
+                f,
+                "page-wise relocation cannot move ${:04X} to ${:04X}",
+                assembled, base
+            ),
+        }
+    }
+}
+
+impl Error for O65Error {}
+
+/// Where a segment was assembled and how long it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct O65Segment {
+    pub base: Word,
+    pub len: Word,
+}
+
+/// The addresses to relocate each segment of an o65 object to. If the object uses
+/// page-wise relocation, each base must sit at the same offset into its page as the
+/// address the segment was assembled at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct O65Bases {
+    pub text: Word,
+    pub data: Word,
+    pub bss: Word,
+    pub zero: Word,
+}
+
+/// How a relocation entry patches the segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RelocationKind {
+    Word,
+    /// The high byte of an address. Unless the object is relocated by whole pages, the
+    /// low byte is kept here to carry into the high byte.
+    High(Byte),
+    Low,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Relocation {
+    /// Offset of the patched byte into its segment.
+    offset: usize,
+    kind: RelocationKind,
+    segment: Byte,
+    /// Index into the undefined references when `segment` is `SEGMENT_UNDEFINED`.
+    undefined: usize,
+}
+
+/// A parsed 6502 o65 relocatable object, see `Mem::load_o65`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct O65 {
+    pub mode: Word,
+    pub text: O65Segment,
+    pub data: O65Segment,
+    pub bss: O65Segment,
+    pub zero: O65Segment,
+    /// Names the object references but does not define.
+    pub undefined: Vec<String>,
+    text_bytes: Vec<Byte>,
+    data_bytes: Vec<Byte>,
+    text_relocations: Vec<Relocation>,
+    data_relocations: Vec<Relocation>,
+    /// Exported names with their segment and value before relocation.
+    exports: Vec<(String, Byte, Word)>,
+}
+
+impl O65 {
+    /// Parses an o65 file with 16-bit sizes. Header options are skipped, and so is
+    /// anything chained after the first object.
+    pub fn parse(file: &[Byte]) -> Result<O65, O65Error> {
+        let mut reader = Reader { file, offset: 0 };
+        if reader.bytes(MAGIC.len())? != MAGIC {
+            return Err(O65Error::BadMagic);
+        }
+        let mode: Word = reader.word()?;
+        if mode & MODE_SIZE32 != 0 {
+            return Err(O65Error::UnsupportedMode(mode));
+        }
+        let mut segment = || -> Result<O65Segment, O65Error> {
+            Ok(O65Segment {
+                base: reader.word()?,
+                len: reader.word()?,
+            })
+        };
+        let text: O65Segment = segment()?;
+        let data: O65Segment = segment()?;
+        let bss: O65Segment = segment()?;
+        let zero: O65Segment = segment()?;
+        // Stack size, which only matters to an operating system.
+        reader.word()?;
+        loop {
+            let len: usize = reader.byte()? as usize;
+            if len == 0 {
+                break;
+            }
+            // The length counts itself and the option type.
+            reader.bytes(len.saturating_sub(1))?;
+        }
+
+        let text_bytes: Vec<Byte> = reader.bytes(text.len as usize)?.to_vec();
+        let data_bytes: Vec<Byte> = reader.bytes(data.len as usize)?.to_vec();
+        let undefined: Vec<String> = (0..reader.word()?)
+            .map(|_| reader.string())
+            .collect::<Result<_, _>>()?;
+        let paged: bool = mode & MODE_PAGED != 0;
+        let text_relocations: Vec<Relocation> =
+            reader.relocations(text_bytes.len(), paged, undefined.len())?;
+        let data_relocations: Vec<Relocation> =
+            reader.relocations(data_bytes.len(), paged, undefined.len())?;
+        let exports: Vec<(String, Byte, Word)> = (0..reader.word()?)
+            .map(|_| Ok((reader.string()?, reader.byte()?, reader.word()?)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(O65 {
+            mode,
+            text,
+            data,
+            bss,
+            zero,
+            undefined,
+            text_bytes,
+            data_bytes,
+            text_relocations,
+            data_relocations,
+            exports,
+        })
+    }
+
+    /// How far `bases` moves each segment, indexed by segment ID.
+    fn deltas(&self, bases: &O65Bases) -> [Word; 6] {
+        let mut deltas: [Word; 6] = [0; 6];
+        deltas[SEGMENT_TEXT as usize] = bases.text.wrapping_sub(self.text.base);
+        deltas[SEGMENT_DATA as usize] = bases.data.wrapping_sub(self.data.base);
+        deltas[SEGMENT_BSS as usize] = bases.bss.wrapping_sub(self.bss.base);
+        deltas[SEGMENT_ZERO as usize] = bases.zero.wrapping_sub(self.zero.base);
+        deltas
+    }
+}
+
+impl Mem {
+    /// Relocates `object` to `bases` and writes its text and data segments to memory,
+    /// clearing bss if the object asks for it. References to undefined symbols are
+    /// resolved from `symbols`. Returns the exported symbols at their relocated
+    /// addresses. Nothing is written if a symbol is missing, a segment does not fit, or
+    /// the object only supports page-wise relocation and a base would move a segment by
+    /// part of a page.
+    ///
+    /// ```
+    /// use emulate_6502::mem::{Mem, O65Bases, O65};
+    /// use std::collections::HashMap;
+    ///
+    /// # let file: Vec<u8> = [
+    /// #     &[0x01, 0x00, b'o', b'6', b'5', 0x00, 0x00, 0x00][..],
+    /// #     &[0x00, 0x10, 0x03, 0x00, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x00],
+    /// #     &[0x4C, 0x00, 0x10], // start: JMP start
+    /// #     &[0x00, 0x00, 0x02, 0x82, 0x00, 0x00],
+    /// #     &[0x01, 0x00, b's', b't', b'a', b'r', b't', 0x00, 0x02, 0x00, 0x10],
+    /// # ]
+    /// # .concat();
+    /// let object = O65::parse(&file).unwrap();
+    /// let bases = O65Bases {
+    ///     text: 0xC000,
+    ///     data: 0xC000 + object.text.len,
+    ///     bss: 0x0400,
+    ///     zero: 0x80,
+    /// };
+    /// let mut memory = Mem::new();
+    /// let exports = memory.load_o65(&object, &bases, &HashMap::new()).unwrap();
+    /// assert_eq!(exports["start"], 0xC000);
+    /// assert_eq!(&memory.data[0xC000..0xC003], &[0x4C, 0x00, 0xC0]);
+    /// ```
+    pub fn load_o65(
+        &mut self,
+        object: &O65,
+        bases: &O65Bases,
+        symbols: &HashMap<String, Word>,
+    ) -> Result<HashMap<String, Word>, O65Error> {
+        let fits = |base: Word, len: Word, top: usize| {
+            if base as usize + len as usize > top {
+                return Err(O65Error::DoesNotFit { base, len });
+            }
+            Ok(())
+        };
+        fits(bases.text, object.text.len, 0x10000)?;
+        fits(bases.data, object.data.len, 0x10000)?;
+        fits(bases.bss, object.bss.len, 0x10000)?;
+        fits(bases.zero, object.zero.len, 0x100)?;
+        if object.mode & MODE_PAGED != 0 {
+            // Only high bytes are relocated, so the low bytes must not change.
+            for (base, segment) in [
+                (bases.text, object.text),
+                (bases.data, object.data),
+                (bases.bss, object.bss),
+                (bases.zero, object.zero),
+            ] {
+                if base & 0xFF != segment.base & 0xFF {
+                    return Err(O65Error::MisalignedBase {
+                        base,
+                        assembled: segment.base,
+                    });
+                }
+            }
+        }
+
+        let deltas: [Word; 6] = object.deltas(bases);
+        let undefined: Vec<Word> = object
+            .undefined
+            .iter()
+            .map(|name| {
+                symbols
+                    .get(name)
+                    .copied()
+                    .ok_or_else(|| O65Error::UndefinedSymbol(name.clone()))
+            })
+            .collect::<Result<_, _>>()?;
+        let mut text: Vec<Byte> = object.text_bytes.clone();
+        relocate(&mut text, &object.text_relocations, &deltas, &undefined);
+        let mut data: Vec<Byte> = object.data_bytes.clone();
+        relocate(&mut data, &object.data_relocations, &deltas, &undefined);
+
+        let text_start: usize = bases.text as usize;
+        self.data[text_start..text_start + text.len()].copy_from_slice(&text);
+        let data_start: usize = bases.data as usize;
+        self.data[data_start..data_start + data.len()].copy_from_slice(&data);
+        if object.mode & MODE_BSS_ZERO != 0 {
+            let bss_start: usize = bases.bss as usize;
+            self.data[bss_start..bss_start + object.bss.len as usize].fill(0);
+        }
+
+        Ok(object
+            .exports
+            .iter()
+            .map(|(name, segment, value)| {
+                let delta: Word = deltas.get(*segment as usize).copied().unwrap_or(0);
+                (name.clone(), value.wrapping_add(delta))
+            })
+            .collect())
+    }
+}
+
+/// Applies a segment's relocation table. Entries were bounds checked by the parser.
+fn relocate(
+    segment: &mut [Byte],
+    relocations: &[Relocation],
+    deltas: &[Word; 6],
+    undefined: &[Word],
+) {
+    for relocation in relocations {
+        let delta: Word = if relocation.segment == SEGMENT_UNDEFINED {
+            undefined[relocation.undefined]
+        } else {
+            deltas[relocation.segment as usize]
+        };
+        let offset: usize = relocation.offset;
+        match relocation.kind {
+            RelocationKind::Word => {
+                let value: Word = Word::from_le_bytes([segment[offset], segment[offset + 1]]);
+                let bytes: [Byte; 2] = value.wrapping_add(delta).to_le_bytes();
+                segment[offset..offset + 2].copy_from_slice(&bytes);
+            }
+            RelocationKind::High(low) => {
+                let value: Word = Word::from_be_bytes([segment[offset], low]);
+                segment[offset] = (value.wrapping_add(delta) >> 8) as Byte;
+            }
+            RelocationKind::Low => {
+                segment[offset] = segment[offset].wrapping_add(delta as Byte);
+            }
+        }
+    }
+}
+
+/// Reads the fields of an o65 file, reporting where it ran out.
+struct Reader<'a> {
+    file: &'a [Byte],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn bytes(&mut self, len: usize) -> Result<&[Byte], O65Error> {
+        let bytes: &[Byte] =
+            self.file
+                .get(self.offset..self.offset + len)
+                .ok_or(O65Error::Truncated {
+                    offset: self.offset,
+                })?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<Byte, O65Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn word(&mut self) -> Result<Word, O65Error> {
+        let bytes: &[Byte] = self.bytes(2)?;
+        Ok(Word::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// A zero-terminated name.
+    fn string(&mut self) -> Result<String, O65Error> {
+        let rest: &[Byte] = &self.file[self.offset.min(self.file.len())..];
+        let len: usize = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(O65Error::Truncated {
+                offset: self.file.len(),
+            })?;
+        let name: String = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.offset += len + 1;
+        Ok(name)
+    }
+
+    /// A relocation table for a segment of `len` bytes. Offsets are cumulative and the
+    /// first one counts from the byte before the segment; 255 skips ahead 254 bytes
+    /// without an entry and 0 ends the table.
+    fn relocations(
+        &mut self,
+        len: usize,
+        paged: bool,
+        undefined_count: usize,
+    ) -> Result<Vec<Relocation>, O65Error> {
+        let mut relocations: Vec<Relocation> = Vec::new();
+        let mut position: isize = -1;
+        loop {
+            let entry: usize = self.offset;
+            let step: Byte = self.byte()?;
+            match step {
+                0 => return Ok(relocations),
+                255 => {
+                    position += 254;
+                    continue;
+                }
+                _ => position += step as isize,
+            }
+            let bad = O65Error::BadRelocation { offset: entry };
+            let type_byte: Byte = self.byte()?;
+            let segment: Byte = type_byte & 0x1F;
+            let undefined: usize = if segment == SEGMENT_UNDEFINED {
+                self.word()? as usize
+            } else {
+                0
+            };
+            let kind: RelocationKind = match type_byte & 0xE0 {
+                RELOC_WORD => RelocationKind::Word,
+                RELOC_HIGH if paged => RelocationKind::High(0),
+                RELOC_HIGH => RelocationKind::High(self.byte()?),
+                RELOC_LOW => RelocationKind::Low,
+                _ => return Err(bad),
+            };
+            let size: isize = if kind == RelocationKind::Word { 2 } else { 1 };
+            let in_bounds: bool = position >= 0 && position + size <= len as isize;
+            let known_segment: bool = match segment {
+                SEGMENT_UNDEFINED => undefined < undefined_count,
+                _ => segment <= SEGMENT_ZERO,
+            };
+            if !in_bounds || !known_segment {
+                return Err(bad);
+            }
+            // Absolute references stay where they are.
+            if segment == SEGMENT_ABSOLUTE {
+                continue;
+            }
+            relocations.push(Relocation {
+                offset: position as usize,
+                kind,
+                segment,
+                undefined,
+            });
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use super::Mem;
+use crate::{Byte, Word};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The BASIC token for `SYS`.
+const TOKEN_SYS: Byte = 0x9E;
+
+/// Where `Mem::load_prg` put a Commodore PRG file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrgInfo {
+    /// The address the payload was loaded at.
+    pub address: Word,
+    /// Payload length, not counting the two header bytes.
+    pub len: usize,
+    /// The address of the first `SYS` in a BASIC stub, e.g. 2061 for
+    /// `10 SYS2061`. This is the number as written, so it does not follow an
+    /// override address.
+    pub sys: Option<Word>,
+}
+
+impl Mem {
+    /// Loads a Commodore PRG image: a little-endian load address followed by the
+    /// payload. `address` overrides the load address from the header. If the payload
+    /// is a tokenized BASIC program, its first `SYS` target is reported.
+    ///
+    /// ```
+    /// use emulate_6502::mem::Mem;
+    ///
+    /// // 10 SYS2061, then machine code at $080D.
+    /// let prg = [
+    ///     0x01, 0x08, 0x0B, 0x08, 0x0A, 0x00, 0x9E, b'2', b'0', b'6', b'1', 0x00, 0x00,
+    ///     0x00, 0x60,
+    /// ];
+    /// let mut memory = Mem::new();
+    /// let info = memory.load_prg(&prg, None).unwrap();
+    /// assert_eq!(info.address, 0x0801);
+    /// assert_eq!(info.sys, Some(0x080D));
+    /// assert_eq!(memory[0x080D], 0x60);
+    /// ```
+    pub fn load_prg(&mut self, data: &[Byte], address: Option<Word>) -> io::Result<PrgInfo> {
+        if data.len() < 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "PRG file is shorter than its load address",
+            ));
+        }
+        let header: Word = Word::from_le_bytes([data[0], data[1]]);
+        let payload: &[Byte] = &data[2..];
+        let address: Word = address.unwrap_or(header);
+        self.load_slice(address, payload)?;
+        Ok(PrgInfo {
+            address,
+            len: payload.len(),
+            sys: find_sys(header, payload),
+        })
+    }
+
+    /// Loads a PRG file from `path`, see `load_prg`.
+    pub fn load_prg_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        address: Option<Word>,
+    ) -> io::Result<PrgInfo> {
+        let data: Vec<Byte> = fs::read(path)?;
+        self.load_prg(&data, address)
+    }
+
+    /// `start..=end` as a PRG image, with `start` as the load address. Fails if
+    /// `start` is above `end`.
+    pub fn to_prg(&self, start: Word, end: Word) -> io::Result<Vec<Byte>> {
+        let mut data: Vec<Byte> = start.to_le_bytes().to_vec();
+        data.extend_from_slice(self.range_bytes(start, end)?);
+        Ok(data)
+    }
+
+    /// Writes `start..=end` to `path` as a PRG file, see `to_prg`.
+    pub fn write_to_prg(&self, path: impl AsRef<Path>, start: Word, end: Word) -> io::Result<()> {
+        fs::write(path, self.to_prg(start, end)?)
+    }
+}
+
+/// Walks `payload` as a tokenized BASIC program loaded at `address` and returns the
+/// number after the first `SYS`. Every line's link must point at the next line, which
+/// keeps machine code that happens to contain $9E from being taken for BASIC.
+fn find_sys(address: Word, payload: &[Byte]) -> Option<Word> {
+    let mut offset: usize = 0;
+    loop {
+        let link: Word = Word::from_le_bytes([*payload.get(offset)?, *payload.get(offset + 1)?]);
+        if link == 0 {
+            return None;
+        }
+        // Skip the link and the line number.
+        let text_start: usize = offset + 4;
+        let text_len: usize = payload.get(text_start..)?.iter().position(|&b| b == 0)?;
+        let next: usize = text_start + text_len + 1;
+        if link as usize != address as usize + next {
+            return None;
+        }
+        let text: &[Byte] = &payload[text_start..text_start + text_len];
+        if let Some(sys) = text.iter().position(|&b| b == TOKEN_SYS) {
+            return parse_sys_argument(&text[sys + 1..]);
+        }
+        offset = next;
+    }
+}
+
+/// Reads the decimal address after `SYS`, allowing spaces and an opening parenthesis.
+fn parse_sys_argument(text: &[Byte]) -> Option<Word> {
+    let digits: Vec<Byte> = text
+        .iter()
+        .copied()
+        .skip_while(|&b| b == b' ' || b == b'(')
+        .take_while(|b| b.is_ascii_digit())
+        .collect();
+    std::str::from_utf8(&digits).ok()?.parse().ok()
+}
@@ -0,0 +1,217 @@
+use super::ihex::{decode_hex, push_hex};
+use super::Mem;
+use crate::{Byte, Word};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A line of a Motorola S-record file that could not be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrecError {
+    /// 1-based line number.
+    pub line: usize,
+    pub kind: SrecErrorKind,
+}
+
+/// What was wrong with a line of a Motorola S-record file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SrecErrorKind {
+    /// The line does not start with `S` and a record type digit.
+    MissingType,
+    /// The line holds something other than pairs of hex digits after the type.
+    BadDigits,
+    /// The byte count does not match the number of bytes that follow it.
+    BadLength { declared: usize, found: usize },
+    /// The checksum does not match the rest of the record.
+    BadChecksum { expected: Byte, found: Byte },
+    /// A record type other than S0, S1, S2, S5, S6, S8 or S9.
+    UnsupportedRecord(char),
+    /// Data or an entry point above $FFFF.
+    OutOfRange(u32),
+    /// The file ended without an S8 or S9 termination record.
+    MissingTermination,
+}
+
+impl fmt::Display for SrecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            SrecErrorKind::MissingType => write!(f, "record does not start with a type"),
+            SrecErrorKind::BadDigits => write!(f, "record is not made of hex byte pairs"),
+            SrecErrorKind::BadLength { declared, found } => write!(
+                f,
+                "byte count is {} but {} bytes follow it",
+                declared, found
+            ),
+            SrecErrorKind::BadChecksum { expected, found } => {
+                write!(f, "checksum is ${:02X}, expected ${:02X}", found, expected)
+            }
+            SrecErrorKind::UnsupportedRecord(record) => {
+                write!(f, "unsupported record type S{}", record)
+            }
+            SrecErrorKind::OutOfRange(address) => {
+                write!(f, "address ${:X} is above $FFFF", address)
+            }
+            SrecErrorKind::MissingTermination => write!(f, "missing termination record"),
+        }
+    }
+}
+
+impl Error for SrecError {}
+
+/// Data bytes per S1 record written by `Mem::to_srec`.
+const BYTES_PER_RECORD: usize = 16;
+
+impl Mem {
+    /// Loads a Motorola S-record image and returns the entry point from its S9 or S8
+    /// termination record, for seeding PC with `Cpu::set_pc`. S1 and S2 data records
+    /// must stay below $10000; S0 headers and S5/S6 counts are skipped. Nothing is
+    /// written unless the whole file is valid.
+    ///
+    /// ```
+    /// use emulate_6502::mem::Mem;
+    ///
+    /// let mut memory = Mem::new();
+    /// let entry = memory.load_srec("S1060200A9428A82\nS9030200FA\n").unwrap();
+    /// assert_eq!(memory[0x0201], 0x42);
+    /// assert_eq!(entry, 0x0200);
+    /// ```
+    pub fn load_srec(&mut self, text: &str) -> Result<Word, SrecError> {
+        let mut chunks: Vec<(Word, Vec<Byte>)> = Vec::new();
+        let mut line_count: usize = 0;
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number: usize = index + 1;
+            line_count = line_number;
+            let line: &str = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |kind: SrecErrorKind| SrecError {
+                line: line_number,
+                kind,
+            };
+            let (record, address, data) = parse_record(line).map_err(error)?;
+            match record {
+                '0' | '5' | '6' => {}
+                '1' | '2' => {
+                    if address as usize + data.len() > 0x10000 {
+                        return Err(error(SrecErrorKind::OutOfRange(
+                            address + data.len() as u32 - 1,
+                        )));
+                    }
+                    chunks.push((address as Word, data));
+                }
+                '8' | '9' => {
+                    if address > 0xFFFF {
+                        return Err(error(SrecErrorKind::OutOfRange(address)));
+                    }
+                    for (address, data) in chunks {
+                        let start: usize = address as usize;
+                        self.data[start..start + data.len()].copy_from_slice(&data);
+                    }
+                    return Ok(address as Word);
+                }
+                _ => unreachable!("parse_record only returns supported records"),
+            }
+        }
+
+        Err(SrecError {
+            line: line_count + 1,
+            kind: SrecErrorKind::MissingTermination,
+        })
+    }
+
+    /// Loads a Motorola S-record file from `path`, see `load_srec`. Malformed lines
+    /// are reported as `InvalidData` errors wrapping an `SrecError`.
+    pub fn load_srec_file(&mut self, path: impl AsRef<Path>) -> io::Result<Word> {
+        let text: String = fs::read_to_string(path)?;
+        self.load_srec(&text)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Formats `start..=end` as S19: an S0 header, S1 records of 16 data bytes and an
+    /// S9 record holding `entry`. Fails if `start` is above `end`.
+    pub fn to_srec(&self, start: Word, end: Word, entry: Word) -> io::Result<String> {
+        let bytes: &[Byte] = self.range_bytes(start, end)?;
+        let mut text = String::new();
+        push_record(&mut text, '0', 0, &[]);
+        for (index, chunk) in bytes.chunks(BYTES_PER_RECORD).enumerate() {
+            let address: Word = start + (index * BYTES_PER_RECORD) as Word;
+            push_record(&mut text, '1', address, chunk);
+        }
+        push_record(&mut text, '9', entry, &[]);
+        Ok(text)
+    }
+
+    /// Writes `start..=end` to `path` as S19, see `to_srec`.
+    pub fn write_to_srec(
+        &self,
+        path: impl AsRef<Path>,
+        start: Word,
+        end: Word,
+        entry: Word,
+    ) -> io::Result<()> {
+        fs::write(path, self.to_srec(start, end, entry)?)
+    }
+}
+
+/// Address bytes in each supported record type.
+fn address_len(record: char) -> Option<usize> {
+    match record {
+        '0' | '1' | '5' | '9' => Some(2),
+        '2' | '6' | '8' => Some(3),
+        _ => None,
+    }
+}
+
+/// Splits `S<type><count><address><data><checksum>` into its type, address and data
+/// after checking the byte count and checksum.
+fn parse_record(line: &str) -> Result<(char, u32, Vec<Byte>), SrecErrorKind> {
+    let mut chars = line.chars();
+    let record: char = match (chars.next(), chars.next()) {
+        (Some('S' | 's'), Some(record)) if record.is_ascii_digit() => record,
+        _ => return Err(SrecErrorKind::MissingType),
+    };
+    let address_len: usize = address_len(record).ok_or(SrecErrorKind::UnsupportedRecord(record))?;
+    let bytes: Vec<Byte> = decode_hex(chars.as_str()).ok_or(SrecErrorKind::BadDigits)?;
+    if bytes.len() < address_len + 2 {
+        return Err(SrecErrorKind::BadDigits);
+    }
+    let declared: usize = bytes[0] as usize;
+    let found: usize = bytes.len() - 1;
+    if declared != found {
+        return Err(SrecErrorKind::BadLength { declared, found });
+    }
+    let (body, checksum) = bytes.split_at(bytes.len() - 1);
+    let expected: Byte = checksum_of(body);
+    if checksum[0] != expected {
+        return Err(SrecErrorKind::BadChecksum {
+            expected,
+            found: checksum[0],
+        });
+    }
+    let address: u32 = body[1..=address_len]
+        .iter()
+        .fold(0, |address, byte| (address << 8) | *byte as u32);
+    Ok((record, address, body[address_len + 1..].to_vec()))
+}
+
+/// One's complement of the sum of the count, address and data bytes.
+fn checksum_of(bytes: &[Byte]) -> Byte {
+    !bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/// Appends a record with a 16-bit address, i.e. S0, S1, S5 or S9.
+fn push_record(text: &mut String, record: char, address: Word, data: &[Byte]) {
+    let mut bytes: Vec<Byte> = vec![(data.len() + 3) as Byte];
+    bytes.extend_from_slice(&address.to_be_bytes());
+    bytes.extend_from_slice(data);
+    bytes.push(checksum_of(&bytes));
+    text.push('S');
+    text.push(record);
+    push_hex(text, &bytes);
+    text.push('\n');
+}
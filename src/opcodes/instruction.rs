@@ -0,0 +1,239 @@
+use crate::Byte;
+use std::fmt;
+
+/// Instruction mnemonics across all supported variants, including the undocumented
+/// NMOS opcodes and the 65C02 additions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mnemonic {
+    Adc,
+    And,
+    Asl,
+    Bcc,
+    Bcs,
+    Beq,
+    Bit,
+    Bmi,
+    Bne,
+    Bpl,
+    Brk,
+    Bvc,
+    Bvs,
+    Clc,
+    Cld,
+    Cli,
+    Clv,
+    Cmp,
+    Cpx,
+    Cpy,
+    Dec,
+    Dex,
+    Dey,
+    Eor,
+    Inc,
+    Inx,
+    Iny,
+    Jmp,
+    Jsr,
+    Lda,
+    Ldx,
+    Ldy,
+    Lsr,
+    Nop,
+    Ora,
+    Pha,
+    Php,
+    Pla,
+    Plp,
+    Rol,
+    Ror,
+    Rti,
+    Rts,
+    Sbc,
+    Sec,
+    Sed,
+    Sei,
+    Sta,
+    Stx,
+    Sty,
+    Tax,
+    Tay,
+    Tsx,
+    Txa,
+    Txs,
+    Tya,
+
+    // Undocumented NMOS
+    Alr,
+    Anc,
+    Ane,
+    Arr,
+    Dcp,
+    Isc,
+    Jam,
+    Las,
+    Lax,
+    Lxa,
+    Rla,
+    Rra,
+    Sax,
+    Sbx,
+    Sha,
+    Shx,
+    Shy,
+    Slo,
+    Sre,
+    Tas,
+
+    // 65C02
+    Bra,
+    Phx,
+    Phy,
+    Plx,
+    Ply,
+    Stz,
+    Trb,
+    Tsb,
+    /// Rockwell and WDC, the bit number is part of the mnemonic
+    Bbr(Byte),
+    Bbs(Byte),
+    Rmb(Byte),
+    Smb(Byte),
+    /// WDC
+    Stp,
+    Wai,
+}
+
+impl Mnemonic {
+    /// Whether this is one of the undocumented NMOS instructions.
+    pub fn is_undocumented(self) -> bool {
+        matches!(
+            self,
+            Mnemonic::Alr
+                | Mnemonic::Anc
+                | Mnemonic::Ane
+                | Mnemonic::Arr
+                | Mnemonic::Dcp
+                | Mnemonic::Isc
+                | Mnemonic::Jam
+                | Mnemonic::Las
+                | Mnemonic::Lax
+                | Mnemonic::Lxa
+                | Mnemonic::Rla
+                | Mnemonic::Rra
+                | Mnemonic::Sax
+                | Mnemonic::Sbx
+                | Mnemonic::Sha
+                | Mnemonic::Shx
+                | Mnemonic::Shy
+                | Mnemonic::Slo
+                | Mnemonic::Sre
+                | Mnemonic::Tas
+        )
+    }
+}
+
+impl fmt::Display for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mnemonic::Bbr(bit) => write!(f, "BBR{}", bit),
+            Mnemonic::Bbs(bit) => write!(f, "BBS{}", bit),
+            Mnemonic::Rmb(bit) => write!(f, "RMB{}", bit),
+            Mnemonic::Smb(bit) => write!(f, "SMB{}", bit),
+            other => f.write_str(&format!("{:?}", other).to_uppercase()),
+        }
+    }
+}
+
+/// How an instruction finds its operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressingMode {
+    /// No operand, e.g. `CLC`.
+    Implied,
+    /// Operates on A, e.g. `ASL A`.
+    Accumulator,
+    /// `#$nn`
+    Immediate,
+    /// `$nn`
+    ZeroPage,
+    /// `$nn,X`, wrapping within page zero
+    ZeroPageX,
+    /// `$nn,Y`, wrapping within page zero
+    ZeroPageY,
+    /// Signed offset from the next instruction, used by branches.
+    Relative,
+    /// `$nnnn`
+    Absolute,
+    /// `$nnnn,X`
+    AbsoluteX,
+    /// `$nnnn,Y`
+    AbsoluteY,
+    /// `($nnnn)`, only used by `JMP`
+    Indirect,
+    /// `($nn,X)`
+    IndirectX,
+    /// `($nn),Y`
+    IndirectY,
+    /// `($nn)`, 65C02 only
+    ZeroPageIndirect,
+    /// `($nnnn,X)`, 65C02 `JMP` only
+    AbsoluteIndirectX,
+    /// `$nn,$rr` - a zero page operand followed by a branch offset, used by BBR/BBS.
+    ZeroPageRelative,
+}
+
+impl AddressingMode {
+    /// Number of operand bytes following the opcode.
+    pub const fn operand_length(self) -> Byte {
+        match self {
+            AddressingMode::Implied | AddressingMode::Accumulator => 0,
+            AddressingMode::Immediate
+            | AddressingMode::ZeroPage
+            | AddressingMode::ZeroPageX
+            | AddressingMode::ZeroPageY
+            | AddressingMode::Relative
+            | AddressingMode::IndirectX
+            | AddressingMode::IndirectY
+            | AddressingMode::ZeroPageIndirect => 1,
+            AddressingMode::Absolute
+            | AddressingMode::AbsoluteX
+            | AddressingMode::AbsoluteY
+            | AddressingMode::Indirect
+            | AddressingMode::AbsoluteIndirectX
+            | AddressingMode::ZeroPageRelative => 2,
+        }
+    }
+}
+
+/// Static metadata for one opcode of one variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub mnemonic: Mnemonic,
+    pub mode: AddressingMode,
+    /// Cycles taken when no page is crossed and no branch is taken.
+    pub cycles: Byte,
+    /// Whether crossing a page adds a cycle. For reads this is the indexed operand
+    /// crossing; for branches it is the target being on another page, on top of the
+    /// one cycle every taken branch costs.
+    pub page_cross_penalty: bool,
+}
+
+impl Instruction {
+    pub const fn new(
+        mnemonic: Mnemonic,
+        mode: AddressingMode,
+        cycles: Byte,
+        page_cross_penalty: bool,
+    ) -> Instruction {
+        Instruction {
+            mnemonic,
+            mode,
+            cycles,
+            page_cross_penalty,
+        }
+    }
+
+    /// Length in bytes, including the opcode.
+    pub const fn length(&self) -> Byte {
+        1 + self.mode.operand_length()
+    }
+}
@@ -1,7 +1,29 @@
+use crate::cpu::Variant;
 use crate::Byte;
+
+mod instruction;
+pub mod table;
+
+pub use instruction::{AddressingMode, Instruction, Mnemonic};
+
 pub enum OpCode {}
 
 impl OpCode {
+    /// Looks up the metadata for `opcode` on `variant`.
+    pub fn decode(variant: Variant, opcode: Byte) -> &'static Instruction {
+        &table::table(variant)[opcode as usize]
+    }
+
+    /// Whether `opcode` is part of the documented instruction set of `variant`. Only
+    /// $EA is a documented NOP: the other NMOS NOPs and the reserved 65C02 opcodes are
+    /// not, and neither is the $EB duplicate of `SBC #`.
+    pub fn is_documented(variant: Variant, opcode: Byte) -> bool {
+        match Self::decode(variant, opcode).mnemonic {
+            Mnemonic::Nop => opcode == Self::INS_NOP,
+            mnemonic => !mnemonic.is_undocumented() && opcode != Self::INS_SBC_IM_EB,
+        }
+    }
+
     pub const INS_JSR: Byte = 0x20;
     pub const INS_NOP: Byte = 0xEA;
     // LDA
@@ -258,4 +280,224 @@ impl OpCode {
 
     // BRK - Force Interrupt
     pub const INS_BRK: Byte = 0x00;
+
+    // Undocumented NMOS opcodes
+
+    // SLO - ASL then ORA
+    pub const INS_SLO_ZP: Byte = 0x07;
+    pub const INS_SLO_ZPX: Byte = 0x17;
+    pub const INS_SLO_ABS: Byte = 0x0F;
+    pub const INS_SLO_ABSX: Byte = 0x1F;
+    pub const INS_SLO_ABSY: Byte = 0x1B;
+    pub const INS_SLO_INDX: Byte = 0x03;
+    pub const INS_SLO_INDY: Byte = 0x13;
+
+    // RLA - ROL then AND
+    pub const INS_RLA_ZP: Byte = 0x27;
+    pub const INS_RLA_ZPX: Byte = 0x37;
+    pub const INS_RLA_ABS: Byte = 0x2F;
+    pub const INS_RLA_ABSX: Byte = 0x3F;
+    pub const INS_RLA_ABSY: Byte = 0x3B;
+    pub const INS_RLA_INDX: Byte = 0x23;
+    pub const INS_RLA_INDY: Byte = 0x33;
+
+    // SRE - LSR then EOR
+    pub const INS_SRE_ZP: Byte = 0x47;
+    pub const INS_SRE_ZPX: Byte = 0x57;
+    pub const INS_SRE_ABS: Byte = 0x4F;
+    pub const INS_SRE_ABSX: Byte = 0x5F;
+    pub const INS_SRE_ABSY: Byte = 0x5B;
+    pub const INS_SRE_INDX: Byte = 0x43;
+    pub const INS_SRE_INDY: Byte = 0x53;
+
+    // RRA - ROR then ADC
+    pub const INS_RRA_ZP: Byte = 0x67;
+    pub const INS_RRA_ZPX: Byte = 0x77;
+    pub const INS_RRA_ABS: Byte = 0x6F;
+    pub const INS_RRA_ABSX: Byte = 0x7F;
+    pub const INS_RRA_ABSY: Byte = 0x7B;
+    pub const INS_RRA_INDX: Byte = 0x63;
+    pub const INS_RRA_INDY: Byte = 0x73;
+
+    // DCP - DEC then CMP
+    pub const INS_DCP_ZP: Byte = 0xC7;
+    pub const INS_DCP_ZPX: Byte = 0xD7;
+    pub const INS_DCP_ABS: Byte = 0xCF;
+    pub const INS_DCP_ABSX: Byte = 0xDF;
+    pub const INS_DCP_ABSY: Byte = 0xDB;
+    pub const INS_DCP_INDX: Byte = 0xC3;
+    pub const INS_DCP_INDY: Byte = 0xD3;
+
+    // ISC - INC then SBC
+    pub const INS_ISC_ZP: Byte = 0xE7;
+    pub const INS_ISC_ZPX: Byte = 0xF7;
+    pub const INS_ISC_ABS: Byte = 0xEF;
+    pub const INS_ISC_ABSX: Byte = 0xFF;
+    pub const INS_ISC_ABSY: Byte = 0xFB;
+    pub const INS_ISC_INDX: Byte = 0xE3;
+    pub const INS_ISC_INDY: Byte = 0xF3;
+
+    // LAX - LDA and LDX
+    pub const INS_LAX_ZP: Byte = 0xA7;
+    pub const INS_LAX_ZPY: Byte = 0xB7;
+    pub const INS_LAX_ABS: Byte = 0xAF;
+    pub const INS_LAX_ABSY: Byte = 0xBF;
+    pub const INS_LAX_INDX: Byte = 0xA3;
+    pub const INS_LAX_INDY: Byte = 0xB3;
+
+    // SAX - Store A AND X
+    pub const INS_SAX_ZP: Byte = 0x87;
+    pub const INS_SAX_ZPY: Byte = 0x97;
+    pub const INS_SAX_ABS: Byte = 0x8F;
+    pub const INS_SAX_INDX: Byte = 0x83;
+
+    // Immediate-only combinations
+    pub const INS_ANC_IM: Byte = 0x0B;
+    pub const INS_ANC_IM_2B: Byte = 0x2B;
+    pub const INS_ALR_IM: Byte = 0x4B;
+    pub const INS_ARR_IM: Byte = 0x6B;
+    pub const INS_SBX_IM: Byte = 0xCB;
+    pub const INS_SBC_IM_EB: Byte = 0xEB;
+
+    // Unstable opcodes
+    pub const INS_ANE_IM: Byte = 0x8B;
+    pub const INS_LXA_IM: Byte = 0xAB;
+    pub const INS_SHA_ABSY: Byte = 0x9F;
+    pub const INS_SHA_INDY: Byte = 0x93;
+    pub const INS_SHX_ABSY: Byte = 0x9E;
+    pub const INS_SHY_ABSX: Byte = 0x9C;
+    pub const INS_TAS_ABSY: Byte = 0x9B;
+    pub const INS_LAS_ABSY: Byte = 0xBB;
+
+    // NOP - undocumented variants
+    pub const INS_NOP_1A: Byte = 0x1A;
+    pub const INS_NOP_3A: Byte = 0x3A;
+    pub const INS_NOP_5A: Byte = 0x5A;
+    pub const INS_NOP_7A: Byte = 0x7A;
+    pub const INS_NOP_DA: Byte = 0xDA;
+    pub const INS_NOP_FA: Byte = 0xFA;
+    pub const INS_NOP_IM_80: Byte = 0x80;
+    pub const INS_NOP_IM_82: Byte = 0x82;
+    pub const INS_NOP_IM_89: Byte = 0x89;
+    pub const INS_NOP_IM_C2: Byte = 0xC2;
+    pub const INS_NOP_IM_E2: Byte = 0xE2;
+    pub const INS_NOP_ZP_04: Byte = 0x04;
+    pub const INS_NOP_ZP_44: Byte = 0x44;
+    pub const INS_NOP_ZP_64: Byte = 0x64;
+    pub const INS_NOP_ZPX_14: Byte = 0x14;
+    pub const INS_NOP_ZPX_34: Byte = 0x34;
+    pub const INS_NOP_ZPX_54: Byte = 0x54;
+    pub const INS_NOP_ZPX_74: Byte = 0x74;
+    pub const INS_NOP_ZPX_D4: Byte = 0xD4;
+    pub const INS_NOP_ZPX_F4: Byte = 0xF4;
+    pub const INS_NOP_ABS_0C: Byte = 0x0C;
+    pub const INS_NOP_ABSX_1C: Byte = 0x1C;
+    pub const INS_NOP_ABSX_3C: Byte = 0x3C;
+    pub const INS_NOP_ABSX_5C: Byte = 0x5C;
+    pub const INS_NOP_ABSX_7C: Byte = 0x7C;
+    pub const INS_NOP_ABSX_DC: Byte = 0xDC;
+    pub const INS_NOP_ABSX_FC: Byte = 0xFC;
+
+    // JAM - Halt the CPU until reset
+    pub const INS_JAM_02: Byte = 0x02;
+    pub const INS_JAM_12: Byte = 0x12;
+    pub const INS_JAM_22: Byte = 0x22;
+    pub const INS_JAM_32: Byte = 0x32;
+    pub const INS_JAM_42: Byte = 0x42;
+    pub const INS_JAM_52: Byte = 0x52;
+    pub const INS_JAM_62: Byte = 0x62;
+    pub const INS_JAM_72: Byte = 0x72;
+    pub const INS_JAM_92: Byte = 0x92;
+    pub const INS_JAM_B2: Byte = 0xB2;
+    pub const INS_JAM_D2: Byte = 0xD2;
+    pub const INS_JAM_F2: Byte = 0xF2;
+
+    // 65C02 additions
+
+    // BRA - Branch Always
+    pub const INS_BRA: Byte = 0x80;
+
+    // STZ - Store Zero
+    pub const INS_STZ_ZP: Byte = 0x64;
+    pub const INS_STZ_ZPX: Byte = 0x74;
+    pub const INS_STZ_ABS: Byte = 0x9C;
+    pub const INS_STZ_ABSX: Byte = 0x9E;
+
+    // PHX, PHY, PLX, PLY - Push and Pull Index Registers
+    pub const INS_PHX: Byte = 0xDA;
+    pub const INS_PHY: Byte = 0x5A;
+    pub const INS_PLX: Byte = 0xFA;
+    pub const INS_PLY: Byte = 0x7A;
+
+    // TRB - Test and Reset Bits
+    pub const INS_TRB_ZP: Byte = 0x14;
+    pub const INS_TRB_ABS: Byte = 0x1C;
+
+    // TSB - Test and Set Bits
+    pub const INS_TSB_ZP: Byte = 0x04;
+    pub const INS_TSB_ABS: Byte = 0x0C;
+
+    // (zp) addressing
+    pub const INS_ORA_INDZP: Byte = 0x12;
+    pub const INS_AND_INDZP: Byte = 0x32;
+    pub const INS_EOR_INDZP: Byte = 0x52;
+    pub const INS_ADC_INDZP: Byte = 0x72;
+    pub const INS_STA_INDZP: Byte = 0x92;
+    pub const INS_LDA_INDZP: Byte = 0xB2;
+    pub const INS_CMP_INDZP: Byte = 0xD2;
+    pub const INS_SBC_INDZP: Byte = 0xF2;
+
+    // New addressing modes for existing instructions
+    pub const INS_BIT_IM: Byte = 0x89;
+    pub const INS_BIT_ZPX: Byte = 0x34;
+    pub const INS_BIT_ABSX: Byte = 0x3C;
+    pub const INS_INC_ACC: Byte = 0x1A;
+    pub const INS_DEC_ACC: Byte = 0x3A;
+    pub const INS_JMP_ABSX_IND: Byte = 0x7C;
+
+    // RMB - Reset Memory Bit (Rockwell and WDC)
+    pub const INS_RMB0: Byte = 0x07;
+    pub const INS_RMB1: Byte = 0x17;
+    pub const INS_RMB2: Byte = 0x27;
+    pub const INS_RMB3: Byte = 0x37;
+    pub const INS_RMB4: Byte = 0x47;
+    pub const INS_RMB5: Byte = 0x57;
+    pub const INS_RMB6: Byte = 0x67;
+    pub const INS_RMB7: Byte = 0x77;
+
+    // SMB - Set Memory Bit (Rockwell and WDC)
+    pub const INS_SMB0: Byte = 0x87;
+    pub const INS_SMB1: Byte = 0x97;
+    pub const INS_SMB2: Byte = 0xA7;
+    pub const INS_SMB3: Byte = 0xB7;
+    pub const INS_SMB4: Byte = 0xC7;
+    pub const INS_SMB5: Byte = 0xD7;
+    pub const INS_SMB6: Byte = 0xE7;
+    pub const INS_SMB7: Byte = 0xF7;
+
+    // BBR - Branch on Bit Reset (Rockwell and WDC)
+    pub const INS_BBR0: Byte = 0x0F;
+    pub const INS_BBR1: Byte = 0x1F;
+    pub const INS_BBR2: Byte = 0x2F;
+    pub const INS_BBR3: Byte = 0x3F;
+    pub const INS_BBR4: Byte = 0x4F;
+    pub const INS_BBR5: Byte = 0x5F;
+    pub const INS_BBR6: Byte = 0x6F;
+    pub const INS_BBR7: Byte = 0x7F;
+
+    // BBS - Branch on Bit Set (Rockwell and WDC)
+    pub const INS_BBS0: Byte = 0x8F;
+    pub const INS_BBS1: Byte = 0x9F;
+    pub const INS_BBS2: Byte = 0xAF;
+    pub const INS_BBS3: Byte = 0xBF;
+    pub const INS_BBS4: Byte = 0xCF;
+    pub const INS_BBS5: Byte = 0xDF;
+    pub const INS_BBS6: Byte = 0xEF;
+    pub const INS_BBS7: Byte = 0xFF;
+
+    // WAI - Wait for Interrupt (WDC)
+    pub const INS_WAI: Byte = 0xCB;
+
+    // STP - Stop the Clock (WDC)
+    pub const INS_STP: Byte = 0xDB;
 }
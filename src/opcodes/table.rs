@@ -0,0 +1,372 @@
+use super::instruction::{AddressingMode::*, Instruction, Mnemonic::*};
+use crate::cpu::Variant;
+use crate::Byte;
+
+/// Returns the opcode table for `variant`. The 2A03 and 6510 decode exactly like the
+/// NMOS 6502.
+pub fn table(variant: Variant) -> &'static [Instruction; 256] {
+    match variant {
+        Variant::Nmos6502 | Variant::Ricoh2A03 | Variant::Mos6510 => &NMOS,
+        Variant::Cmos65C02 => &CMOS,
+        Variant::Rockwell65C02 => &ROCKWELL,
+        Variant::Wdc65C02 => &WDC,
+    }
+}
+
+/// NMOS 6502, including the undocumented opcodes.
+pub static NMOS: [Instruction; 256] = NMOS_TABLE;
+
+/// 65C02 without the Rockwell bit instructions.
+pub static CMOS: [Instruction; 256] = cmos_table();
+
+/// Rockwell R65C02.
+pub static ROCKWELL: [Instruction; 256] = rockwell_table();
+
+/// WDC W65C02S.
+pub static WDC: [Instruction; 256] = wdc_table();
+
+const NMOS_TABLE: [Instruction; 256] = [
+    /* $00 */ Instruction::new(Brk, Implied, 7, false),
+    /* $01 */ Instruction::new(Ora, IndirectX, 6, false),
+    /* $02 */ Instruction::new(Jam, Implied, 2, false),
+    /* $03 */ Instruction::new(Slo, IndirectX, 8, false),
+    /* $04 */ Instruction::new(Nop, ZeroPage, 3, false),
+    /* $05 */ Instruction::new(Ora, ZeroPage, 3, false),
+    /* $06 */ Instruction::new(Asl, ZeroPage, 5, false),
+    /* $07 */ Instruction::new(Slo, ZeroPage, 5, false),
+    /* $08 */ Instruction::new(Php, Implied, 3, false),
+    /* $09 */ Instruction::new(Ora, Immediate, 2, false),
+    /* $0A */ Instruction::new(Asl, Accumulator, 2, false),
+    /* $0B */ Instruction::new(Anc, Immediate, 2, false),
+    /* $0C */ Instruction::new(Nop, Absolute, 4, false),
+    /* $0D */ Instruction::new(Ora, Absolute, 4, false),
+    /* $0E */ Instruction::new(Asl, Absolute, 6, false),
+    /* $0F */ Instruction::new(Slo, Absolute, 6, false),
+    /* $10 */ Instruction::new(Bpl, Relative, 2, true),
+    /* $11 */ Instruction::new(Ora, IndirectY, 5, true),
+    /* $12 */ Instruction::new(Jam, Implied, 2, false),
+    /* $13 */ Instruction::new(Slo, IndirectY, 8, false),
+    /* $14 */ Instruction::new(Nop, ZeroPageX, 4, false),
+    /* $15 */ Instruction::new(Ora, ZeroPageX, 4, false),
+    /* $16 */ Instruction::new(Asl, ZeroPageX, 6, false),
+    /* $17 */ Instruction::new(Slo, ZeroPageX, 6, false),
+    /* $18 */ Instruction::new(Clc, Implied, 2, false),
+    /* $19 */ Instruction::new(Ora, AbsoluteY, 4, true),
+    /* $1A */ Instruction::new(Nop, Implied, 2, false),
+    /* $1B */ Instruction::new(Slo, AbsoluteY, 7, false),
+    /* $1C */ Instruction::new(Nop, AbsoluteX, 4, true),
+    /* $1D */ Instruction::new(Ora, AbsoluteX, 4, true),
+    /* $1E */ Instruction::new(Asl, AbsoluteX, 7, false),
+    /* $1F */ Instruction::new(Slo, AbsoluteX, 7, false),
+    /* $20 */ Instruction::new(Jsr, Absolute, 6, false),
+    /* $21 */ Instruction::new(And, IndirectX, 6, false),
+    /* $22 */ Instruction::new(Jam, Implied, 2, false),
+    /* $23 */ Instruction::new(Rla, IndirectX, 8, false),
+    /* $24 */ Instruction::new(Bit, ZeroPage, 3, false),
+    /* $25 */ Instruction::new(And, ZeroPage, 3, false),
+    /* $26 */ Instruction::new(Rol, ZeroPage, 5, false),
+    /* $27 */ Instruction::new(Rla, ZeroPage, 5, false),
+    /* $28 */ Instruction::new(Plp, Implied, 4, false),
+    /* $29 */ Instruction::new(And, Immediate, 2, false),
+    /* $2A */ Instruction::new(Rol, Accumulator, 2, false),
+    /* $2B */ Instruction::new(Anc, Immediate, 2, false),
+    /* $2C */ Instruction::new(Bit, Absolute, 4, false),
+    /* $2D */ Instruction::new(And, Absolute, 4, false),
+    /* $2E */ Instruction::new(Rol, Absolute, 6, false),
+    /* $2F */ Instruction::new(Rla, Absolute, 6, false),
+    /* $30 */ Instruction::new(Bmi, Relative, 2, true),
+    /* $31 */ Instruction::new(And, IndirectY, 5, true),
+    /* $32 */ Instruction::new(Jam, Implied, 2, false),
+    /* $33 */ Instruction::new(Rla, IndirectY, 8, false),
+    /* $34 */ Instruction::new(Nop, ZeroPageX, 4, false),
+    /* $35 */ Instruction::new(And, ZeroPageX, 4, false),
+    /* $36 */ Instruction::new(Rol, ZeroPageX, 6, false),
+    /* $37 */ Instruction::new(Rla, ZeroPageX, 6, false),
+    /* $38 */ Instruction::new(Sec, Implied, 2, false),
+    /* $39 */ Instruction::new(And, AbsoluteY, 4, true),
+    /* $3A */ Instruction::new(Nop, Implied, 2, false),
+    /* $3B */ Instruction::new(Rla, AbsoluteY, 7, false),
+    /* $3C */ Instruction::new(Nop, AbsoluteX, 4, true),
+    /* $3D */ Instruction::new(And, AbsoluteX, 4, true),
+    /* $3E */ Instruction::new(Rol, AbsoluteX, 7, false),
+    /* $3F */ Instruction::new(Rla, AbsoluteX, 7, false),
+    /* $40 */ Instruction::new(Rti, Implied, 6, false),
+    /* $41 */ Instruction::new(Eor, IndirectX, 6, false),
+    /* $42 */ Instruction::new(Jam, Implied, 2, false),
+    /* $43 */ Instruction::new(Sre, IndirectX, 8, false),
+    /* $44 */ Instruction::new(Nop, ZeroPage, 3, false),
+    /* $45 */ Instruction::new(Eor, ZeroPage, 3, false),
+    /* $46 */ Instruction::new(Lsr, ZeroPage, 5, false),
+    /* $47 */ Instruction::new(Sre, ZeroPage, 5, false),
+    /* $48 */ Instruction::new(Pha, Implied, 3, false),
+    /* $49 */ Instruction::new(Eor, Immediate, 2, false),
+    /* $4A */ Instruction::new(Lsr, Accumulator, 2, false),
+    /* $4B */ Instruction::new(Alr, Immediate, 2, false),
+    /* $4C */ Instruction::new(Jmp, Absolute, 3, false),
+    /* $4D */ Instruction::new(Eor, Absolute, 4, false),
+    /* $4E */ Instruction::new(Lsr, Absolute, 6, false),
+    /* $4F */ Instruction::new(Sre, Absolute, 6, false),
+    /* $50 */ Instruction::new(Bvc, Relative, 2, true),
+    /* $51 */ Instruction::new(Eor, IndirectY, 5, true),
+    /* $52 */ Instruction::new(Jam, Implied, 2, false),
+    /* $53 */ Instruction::new(Sre, IndirectY, 8, false),
+    /* $54 */ Instruction::new(Nop, ZeroPageX, 4, false),
+    /* $55 */ Instruction::new(Eor, ZeroPageX, 4, false),
+    /* $56 */ Instruction::new(Lsr, ZeroPageX, 6, false),
+    /* $57 */ Instruction::new(Sre, ZeroPageX, 6, false),
+    /* $58 */ Instruction::new(Cli, Implied, 2, false),
+    /* $59 */ Instruction::new(Eor, AbsoluteY, 4, true),
+    /* $5A */ Instruction::new(Nop, Implied, 2, false),
+    /* $5B */ Instruction::new(Sre, AbsoluteY, 7, false),
+    /* $5C */ Instruction::new(Nop, AbsoluteX, 4, true),
+    /* $5D */ Instruction::new(Eor, AbsoluteX, 4, true),
+    /* $5E */ Instruction::new(Lsr, AbsoluteX, 7, false),
+    /* $5F */ Instruction::new(Sre, AbsoluteX, 7, false),
+    /* $60 */ Instruction::new(Rts, Implied, 6, false),
+    /* $61 */ Instruction::new(Adc, IndirectX, 6, false),
+    /* $62 */ Instruction::new(Jam, Implied, 2, false),
+    /* $63 */ Instruction::new(Rra, IndirectX, 8, false),
+    /* $64 */ Instruction::new(Nop, ZeroPage, 3, false),
+    /* $65 */ Instruction::new(Adc, ZeroPage, 3, false),
+    /* $66 */ Instruction::new(Ror, ZeroPage, 5, false),
+    /* $67 */ Instruction::new(Rra, ZeroPage, 5, false),
+    /* $68 */ Instruction::new(Pla, Implied, 4, false),
+    /* $69 */ Instruction::new(Adc, Immediate, 2, false),
+    /* $6A */ Instruction::new(Ror, Accumulator, 2, false),
+    /* $6B */ Instruction::new(Arr, Immediate, 2, false),
+    /* $6C */ Instruction::new(Jmp, Indirect, 5, false),
+    /* $6D */ Instruction::new(Adc, Absolute, 4, false),
+    /* $6E */ Instruction::new(Ror, Absolute, 6, false),
+    /* $6F */ Instruction::new(Rra, Absolute, 6, false),
+    /* $70 */ Instruction::new(Bvs, Relative, 2, true),
+    /* $71 */ Instruction::new(Adc, IndirectY, 5, true),
+    /* $72 */ Instruction::new(Jam, Implied, 2, false),
+    /* $73 */ Instruction::new(Rra, IndirectY, 8, false),
+    /* $74 */ Instruction::new(Nop, ZeroPageX, 4, false),
+    /* $75 */ Instruction::new(Adc, ZeroPageX, 4, false),
+    /* $76 */ Instruction::new(Ror, ZeroPageX, 6, false),
+    /* $77 */ Instruction::new(Rra, ZeroPageX, 6, false),
+    /* $78 */ Instruction::new(Sei, Implied, 2, false),
+    /* $79 */ Instruction::new(Adc, AbsoluteY, 4, true),
+    /* $7A */ Instruction::new(Nop, Implied, 2, false),
+    /* $7B */ Instruction::new(Rra, AbsoluteY, 7, false),
+    /* $7C */ Instruction::new(Nop, AbsoluteX, 4, true),
+    /* $7D */ Instruction::new(Adc, AbsoluteX, 4, true),
+    /* $7E */ Instruction::new(Ror, AbsoluteX, 7, false),
+    /* $7F */ Instruction::new(Rra, AbsoluteX, 7, false),
+    /* $80 */ Instruction::new(Nop, Immediate, 2, false),
+    /* $81 */ Instruction::new(Sta, IndirectX, 6, false),
+    /* $82 */ Instruction::new(Nop, Immediate, 2, false),
+    /* $83 */ Instruction::new(Sax, IndirectX, 6, false),
+    /* $84 */ Instruction::new(Sty, ZeroPage, 3, false),
+    /* $85 */ Instruction::new(Sta, ZeroPage, 3, false),
+    /* $86 */ Instruction::new(Stx, ZeroPage, 3, false),
+    /* $87 */ Instruction::new(Sax, ZeroPage, 3, false),
+    /* $88 */ Instruction::new(Dey, Implied, 2, false),
+    /* $89 */ Instruction::new(Nop, Immediate, 2, false),
+    /* $8A */ Instruction::new(Txa, Implied, 2, false),
+    /* $8B */ Instruction::new(Ane, Immediate, 2, false),
+    /* $8C */ Instruction::new(Sty, Absolute, 4, false),
+    /* $8D */ Instruction::new(Sta, Absolute, 4, false),
+    /* $8E */ Instruction::new(Stx, Absolute, 4, false),
+    /* $8F */ Instruction::new(Sax, Absolute, 4, false),
+    /* $90 */ Instruction::new(Bcc, Relative, 2, true),
+    /* $91 */ Instruction::new(Sta, IndirectY, 6, false),
+    /* $92 */ Instruction::new(Jam, Implied, 2, false),
+    /* $93 */ Instruction::new(Sha, IndirectY, 6, false),
+    /* $94 */ Instruction::new(Sty, ZeroPageX, 4, false),
+    /* $95 */ Instruction::new(Sta, ZeroPageX, 4, false),
+    /* $96 */ Instruction::new(Stx, ZeroPageY, 4, false),
+    /* $97 */ Instruction::new(Sax, ZeroPageY, 4, false),
+    /* $98 */ Instruction::new(Tya, Implied, 2, false),
+    /* $99 */ Instruction::new(Sta, AbsoluteY, 5, false),
+    /* $9A */ Instruction::new(Txs, Implied, 2, false),
+    /* $9B */ Instruction::new(Tas, AbsoluteY, 5, false),
+    /* $9C */ Instruction::new(Shy, AbsoluteX, 5, false),
+    /* $9D */ Instruction::new(Sta, AbsoluteX, 5, false),
+    /* $9E */ Instruction::new(Shx, AbsoluteY, 5, false),
+    /* $9F */ Instruction::new(Sha, AbsoluteY, 5, false),
+    /* $A0 */ Instruction::new(Ldy, Immediate, 2, false),
+    /* $A1 */ Instruction::new(Lda, IndirectX, 6, false),
+    /* $A2 */ Instruction::new(Ldx, Immediate, 2, false),
+    /* $A3 */ Instruction::new(Lax, IndirectX, 6, false),
+    /* $A4 */ Instruction::new(Ldy, ZeroPage, 3, false),
+    /* $A5 */ Instruction::new(Lda, ZeroPage, 3, false),
+    /* $A6 */ Instruction::new(Ldx, ZeroPage, 3, false),
+    /* $A7 */ Instruction::new(Lax, ZeroPage, 3, false),
+    /* $A8 */ Instruction::new(Tay, Implied, 2, false),
+    /* $A9 */ Instruction::new(Lda, Immediate, 2, false),
+    /* $AA */ Instruction::new(Tax, Implied, 2, false),
+    /* $AB */ Instruction::new(Lxa, Immediate, 2, false),
+    /* $AC */ Instruction::new(Ldy, Absolute, 4, false),
+    /* $AD */ Instruction::new(Lda, Absolute, 4, false),
+    /* $AE */ Instruction::new(Ldx, Absolute, 4, false),
+    /* $AF */ Instruction::new(Lax, Absolute, 4, false),
+    /* $B0 */ Instruction::new(Bcs, Relative, 2, true),
+    /* $B1 */ Instruction::new(Lda, IndirectY, 5, true),
+    /* $B2 */ Instruction::new(Jam, Implied, 2, false),
+    /* $B3 */ Instruction::new(Lax, IndirectY, 5, true),
+    /* $B4 */ Instruction::new(Ldy, ZeroPageX, 4, false),
+    /* $B5 */ Instruction::new(Lda, ZeroPageX, 4, false),
+    /* $B6 */ Instruction::new(Ldx, ZeroPageY, 4, false),
+    /* $B7 */ Instruction::new(Lax, ZeroPageY, 4, false),
+    /* $B8 */ Instruction::new(Clv, Implied, 2, false),
+    /* $B9 */ Instruction::new(Lda, AbsoluteY, 4, true),
+    /* $BA */ Instruction::new(Tsx, Implied, 2, false),
+    /* $BB */ Instruction::new(Las, AbsoluteY, 4, true),
+    /* $BC */ Instruction::new(Ldy, AbsoluteX, 4, true),
+    /* $BD */ Instruction::new(Lda, AbsoluteX, 4, true),
+    /* $BE */ Instruction::new(Ldx, AbsoluteY, 4, true),
+    /* $BF */ Instruction::new(Lax, AbsoluteY, 4, true),
+    /* $C0 */ Instruction::new(Cpy, Immediate, 2, false),
+    /* $C1 */ Instruction::new(Cmp, IndirectX, 6, false),
+    /* $C2 */ Instruction::new(Nop, Immediate, 2, false),
+    /* $C3 */ Instruction::new(Dcp, IndirectX, 8, false),
+    /* $C4 */ Instruction::new(Cpy, ZeroPage, 3, false),
+    /* $C5 */ Instruction::new(Cmp, ZeroPage, 3, false),
+    /* $C6 */ Instruction::new(Dec, ZeroPage, 5, false),
+    /* $C7 */ Instruction::new(Dcp, ZeroPage, 5, false),
+    /* $C8 */ Instruction::new(Iny, Implied, 2, false),
+    /* $C9 */ Instruction::new(Cmp, Immediate, 2, false),
+    /* $CA */ Instruction::new(Dex, Implied, 2, false),
+    /* $CB */ Instruction::new(Sbx, Immediate, 2, false),
+    /* $CC */ Instruction::new(Cpy, Absolute, 4, false),
+    /* $CD */ Instruction::new(Cmp, Absolute, 4, false),
+    /* $CE */ Instruction::new(Dec, Absolute, 6, false),
+    /* $CF */ Instruction::new(Dcp, Absolute, 6, false),
+    /* $D0 */ Instruction::new(Bne, Relative, 2, true),
+    /* $D1 */ Instruction::new(Cmp, IndirectY, 5, true),
+    /* $D2 */ Instruction::new(Jam, Implied, 2, false),
+    /* $D3 */ Instruction::new(Dcp, IndirectY, 8, false),
+    /* $D4 */ Instruction::new(Nop, ZeroPageX, 4, false),
+    /* $D5 */ Instruction::new(Cmp, ZeroPageX, 4, false),
+    /* $D6 */ Instruction::new(Dec, ZeroPageX, 6, false),
+    /* $D7 */ Instruction::new(Dcp, ZeroPageX, 6, false),
+    /* $D8 */ Instruction::new(Cld, Implied, 2, false),
+    /* $D9 */ Instruction::new(Cmp, AbsoluteY, 4, true),
+    /* $DA */ Instruction::new(Nop, Implied, 2, false),
+    /* $DB */ Instruction::new(Dcp, AbsoluteY, 7, false),
+    /* $DC */ Instruction::new(Nop, AbsoluteX, 4, true),
+    /* $DD */ Instruction::new(Cmp, AbsoluteX, 4, true),
+    /* $DE */ Instruction::new(Dec, AbsoluteX, 7, false),
+    /* $DF */ Instruction::new(Dcp, AbsoluteX, 7, false),
+    /* $E0 */ Instruction::new(Cpx, Immediate, 2, false),
+    /* $E1 */ Instruction::new(Sbc, IndirectX, 6, false),
+    /* $E2 */ Instruction::new(Nop, Immediate, 2, false),
+    /* $E3 */ Instruction::new(Isc, IndirectX, 8, false),
+    /* $E4 */ Instruction::new(Cpx, ZeroPage, 3, false),
+    /* $E5 */ Instruction::new(Sbc, ZeroPage, 3, false),
+    /* $E6 */ Instruction::new(Inc, ZeroPage, 5, false),
+    /* $E7 */ Instruction::new(Isc, ZeroPage, 5, false),
+    /* $E8 */ Instruction::new(Inx, Implied, 2, false),
+    /* $E9 */ Instruction::new(Sbc, Immediate, 2, false),
+    /* $EA */ Instruction::new(Nop, Implied, 2, false),
+    /* $EB */ Instruction::new(Sbc, Immediate, 2, false),
+    /* $EC */ Instruction::new(Cpx, Absolute, 4, false),
+    /* $ED */ Instruction::new(Sbc, Absolute, 4, false),
+    /* $EE */ Instruction::new(Inc, Absolute, 6, false),
+    /* $EF */ Instruction::new(Isc, Absolute, 6, false),
+    /* $F0 */ Instruction::new(Beq, Relative, 2, true),
+    /* $F1 */ Instruction::new(Sbc, IndirectY, 5, true),
+    /* $F2 */ Instruction::new(Jam, Implied, 2, false),
+    /* $F3 */ Instruction::new(Isc, IndirectY, 8, false),
+    /* $F4 */ Instruction::new(Nop, ZeroPageX, 4, false),
+    /* $F5 */ Instruction::new(Sbc, ZeroPageX, 4, false),
+    /* $F6 */ Instruction::new(Inc, ZeroPageX, 6, false),
+    /* $F7 */ Instruction::new(Isc, ZeroPageX, 6, false),
+    /* $F8 */ Instruction::new(Sed, Implied, 2, false),
+    /* $F9 */ Instruction::new(Sbc, AbsoluteY, 4, true),
+    /* $FA */ Instruction::new(Nop, Implied, 2, false),
+    /* $FB */ Instruction::new(Isc, AbsoluteY, 7, false),
+    /* $FC */ Instruction::new(Nop, AbsoluteX, 4, true),
+    /* $FD */ Instruction::new(Sbc, AbsoluteX, 4, true),
+    /* $FE */ Instruction::new(Inc, AbsoluteX, 7, false),
+    /* $FF */ Instruction::new(Isc, AbsoluteX, 7, false),
+];
+
+/// Starts from the NMOS table, turns every undocumented opcode into a NOP and then
+/// applies the 65C02 additions and timing changes.
+const fn cmos_table() -> [Instruction; 256] {
+    let mut table: [Instruction; 256] = NMOS_TABLE;
+
+    // The unused opcodes are NOPs whose length and timing depend on their column.
+    let mut opcode: usize = 0;
+    while opcode < 256 {
+        let nop: Option<Instruction> = match opcode {
+            0x02 | 0x22 | 0x42 | 0x62 | 0x82 | 0xC2 | 0xE2 => {
+                Some(Instruction::new(Nop, Immediate, 2, false))
+            }
+            0x44 => Some(Instruction::new(Nop, ZeroPage, 3, false)),
+            0x54 | 0xD4 | 0xF4 => Some(Instruction::new(Nop, ZeroPageX, 4, false)),
+            0x5C => Some(Instruction::new(Nop, Absolute, 8, false)),
+            0xDC | 0xFC => Some(Instruction::new(Nop, Absolute, 4, false)),
+            _ if opcode & 0x03 == 0x03 => Some(Instruction::new(Nop, Implied, 1, false)),
+            _ => None,
+        };
+        if let Some(nop) = nop {
+            table[opcode] = nop;
+        }
+        opcode += 1;
+    }
+
+    table[0x80] = Instruction::new(Bra, Relative, 2, true);
+    table[0x64] = Instruction::new(Stz, ZeroPage, 3, false);
+    table[0x74] = Instruction::new(Stz, ZeroPageX, 4, false);
+    table[0x9C] = Instruction::new(Stz, Absolute, 4, false);
+    table[0x9E] = Instruction::new(Stz, AbsoluteX, 5, false);
+    table[0xDA] = Instruction::new(Phx, Implied, 3, false);
+    table[0x5A] = Instruction::new(Phy, Implied, 3, false);
+    table[0xFA] = Instruction::new(Plx, Implied, 4, false);
+    table[0x7A] = Instruction::new(Ply, Implied, 4, false);
+    table[0x14] = Instruction::new(Trb, ZeroPage, 5, false);
+    table[0x1C] = Instruction::new(Trb, Absolute, 6, false);
+    table[0x04] = Instruction::new(Tsb, ZeroPage, 5, false);
+    table[0x0C] = Instruction::new(Tsb, Absolute, 6, false);
+    table[0x12] = Instruction::new(Ora, ZeroPageIndirect, 5, false);
+    table[0x32] = Instruction::new(And, ZeroPageIndirect, 5, false);
+    table[0x52] = Instruction::new(Eor, ZeroPageIndirect, 5, false);
+    table[0x72] = Instruction::new(Adc, ZeroPageIndirect, 5, false);
+    table[0x92] = Instruction::new(Sta, ZeroPageIndirect, 5, false);
+    table[0xB2] = Instruction::new(Lda, ZeroPageIndirect, 5, false);
+    table[0xD2] = Instruction::new(Cmp, ZeroPageIndirect, 5, false);
+    table[0xF2] = Instruction::new(Sbc, ZeroPageIndirect, 5, false);
+    table[0x89] = Instruction::new(Bit, Immediate, 2, false);
+    table[0x34] = Instruction::new(Bit, ZeroPageX, 4, false);
+    table[0x3C] = Instruction::new(Bit, AbsoluteX, 4, true);
+    table[0x1A] = Instruction::new(Inc, Accumulator, 2, false);
+    table[0x3A] = Instruction::new(Dec, Accumulator, 2, false);
+    table[0x7C] = Instruction::new(Jmp, AbsoluteIndirectX, 6, false);
+
+    // JMP ($xxFF) no longer wraps within the page, which costs a cycle.
+    table[0x6C] = Instruction::new(Jmp, Indirect, 6, false);
+    // Shifts and rotates only pay for abs,X when the index crosses a page.
+    table[0x1E] = Instruction::new(Asl, AbsoluteX, 6, true);
+    table[0x3E] = Instruction::new(Rol, AbsoluteX, 6, true);
+    table[0x5E] = Instruction::new(Lsr, AbsoluteX, 6, true);
+    table[0x7E] = Instruction::new(Ror, AbsoluteX, 6, true);
+
+    table
+}
+
+/// Adds BBR, BBS, RMB and SMB in the x7 and xF columns.
+const fn rockwell_table() -> [Instruction; 256] {
+    let mut table: [Instruction; 256] = cmos_table();
+    let mut bit: Byte = 0;
+    while bit < 8 {
+        let row: usize = (bit as usize) << 4;
+        table[0x07 | row] = Instruction::new(Rmb(bit), ZeroPage, 5, false);
+        table[0x87 | row] = Instruction::new(Smb(bit), ZeroPage, 5, false);
+        table[0x0F | row] = Instruction::new(Bbr(bit), ZeroPageRelative, 5, true);
+        table[0x8F | row] = Instruction::new(Bbs(bit), ZeroPageRelative, 5, true);
+        bit += 1;
+    }
+    table
+}
+
+/// Adds WAI and STP.
+const fn wdc_table() -> [Instruction; 256] {
+    let mut table: [Instruction; 256] = rockwell_table();
+    table[0xCB] = Instruction::new(Wai, Implied, 3, false);
+    table[0xDB] = Instruction::new(Stp, Implied, 3, false);
+    table
+}